@@ -0,0 +1,53 @@
+use ansi_term::Style;
+
+/// Text attributes of an element of the bar, e.g. `"bold,italic"`
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct TextAttributes {
+    pub bold: bool,
+    pub dim: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub reverse: bool,
+    pub strikethrough: bool,
+}
+
+impl TextAttributes {
+    pub fn parse(attributes_str: &str) -> Result<Self, String> {
+        let mut attributes = Self::default();
+        for attribute in attributes_str.split(',').map(str::trim) {
+            match attribute {
+                "" | "none" => {}
+                "bold" => attributes.bold = true,
+                "dim" => attributes.dim = true,
+                "italic" => attributes.italic = true,
+                "underline" => attributes.underline = true,
+                "reverse" => attributes.reverse = true,
+                "strikethrough" => attributes.strikethrough = true,
+                _ => return Err(format!("unknown attribute {attribute}")),
+            }
+        }
+        Ok(attributes)
+    }
+
+    pub fn apply(self, mut style: Style) -> Style {
+        if self.bold {
+            style = style.bold();
+        }
+        if self.dim {
+            style = style.dimmed();
+        }
+        if self.italic {
+            style = style.italic();
+        }
+        if self.underline {
+            style = style.underline();
+        }
+        if self.reverse {
+            style = style.reverse();
+        }
+        if self.strikethrough {
+            style = style.strikethrough();
+        }
+        style
+    }
+}
@@ -0,0 +1,259 @@
+use std::collections::BTreeMap;
+
+use zellij_tile::prelude::*;
+
+// guards against settings that reference each other in a loop
+const MAX_REFERENCE_DEPTH: usize = 16;
+const REFERENCE_CYCLE: &str = "references itself";
+
+const ANSI_COLOR_NAMES: [&str; 8] = [
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+];
+
+// the first 16 colors of the xterm palette
+const ANSI_RGB: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+fn to_rgb(color: PaletteColor) -> (u8, u8, u8) {
+    match color {
+        PaletteColor::Rgb(rgb) => rgb,
+        PaletteColor::EightBit(index @ 0..=15) => ANSI_RGB[index as usize],
+        PaletteColor::EightBit(index @ 16..=231) => {
+            let level = |value: u8| if value == 0 { 0 } else { 55 + value * 40 };
+            let index = index - 16;
+            (level(index / 36), level(index / 6 % 6), level(index % 6))
+        }
+        PaletteColor::EightBit(index) => {
+            let gray = 8 + (index - 232) * 10;
+            (gray, gray, gray)
+        }
+    }
+}
+
+// `weight` is the proportion of `a` in the result
+pub fn mix(a: PaletteColor, b: PaletteColor, weight: f64) -> PaletteColor {
+    let (a, b) = (to_rgb(a), to_rgb(b));
+    let channel = |a: u8, b: u8| (a as f64 * weight + b as f64 * (1.0 - weight)).round() as u8;
+    PaletteColor::Rgb((channel(a.0, b.0), channel(a.1, b.1), channel(a.2, b.2)))
+}
+
+pub fn parse_amount(amount: &str) -> Result<f64, String> {
+    let amount = amount.trim();
+    let value = match amount.strip_suffix('%') {
+        Some(percentage) => percentage.trim().parse::<f64>().map(|p| p / 100.0),
+        None => amount.parse::<f64>(),
+    }
+    .map_err(|_| format!("{amount} is not an amount"))?;
+    if !(0.0..=1.0).contains(&value) {
+        return Err(format!("{amount} is not between 0 and 1 (or 0% and 100%)"));
+    }
+    Ok(value)
+}
+
+// splits function arguments on the commas that aren't inside nested calls
+fn split_arguments(arguments: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in arguments.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(&arguments[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&arguments[start..]);
+    parts
+}
+
+fn parse_hex_color(hex: &str) -> Option<PaletteColor> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |i: usize, len: usize| u8::from_str_radix(&hex[i * len..(i + 1) * len], 16).ok();
+    match hex.len() {
+        // #rgb is shorthand for #rrggbb
+        3 => Some(PaletteColor::Rgb((
+            channel(0, 1)? * 0x11,
+            channel(1, 1)? * 0x11,
+            channel(2, 1)? * 0x11,
+        ))),
+        6 => Some(PaletteColor::Rgb((
+            channel(0, 2)?,
+            channel(1, 2)?,
+            channel(2, 2)?,
+        ))),
+        _ => None,
+    }
+}
+
+fn parse_ansi_color_name(name: &str) -> Option<PaletteColor> {
+    let (name, offset) = match name.strip_prefix("bright_") {
+        Some(name) => (name, 8),
+        None => (name, 0),
+    };
+    let index = ANSI_COLOR_NAMES.iter().position(|n| *n == name)?;
+    Some(PaletteColor::EightBit(index as u8 + offset))
+}
+
+fn parse_theme_color(reference: &str, colors: &Styling) -> Result<PaletteColor, String> {
+    let Some((declaration_name, slot)) = reference.split_once('.') else {
+        return Err("expected theme.{style}.{color}".to_string());
+    };
+    let declaration = match declaration_name {
+        "text_unselected" => colors.text_unselected,
+        "text_selected" => colors.text_selected,
+        "ribbon_unselected" => colors.ribbon_unselected,
+        "ribbon_selected" => colors.ribbon_selected,
+        "table_title" => colors.table_title,
+        "table_cell_unselected" => colors.table_cell_unselected,
+        "table_cell_selected" => colors.table_cell_selected,
+        "list_unselected" => colors.list_unselected,
+        "list_selected" => colors.list_selected,
+        "frame_unselected" => colors
+            .frame_unselected
+            .ok_or("the theme doesn't define frame_unselected")?,
+        "frame_selected" => colors.frame_selected,
+        "frame_highlight" => colors.frame_highlight,
+        "exit_code_success" => colors.exit_code_success,
+        "exit_code_error" => colors.exit_code_error,
+        _ => return Err(format!("unknown theme style {declaration_name}")),
+    };
+    Ok(match slot {
+        "base" => declaration.base,
+        "background" => declaration.background,
+        "emphasis_0" => declaration.emphasis_0,
+        "emphasis_1" => declaration.emphasis_1,
+        "emphasis_2" => declaration.emphasis_2,
+        "emphasis_3" => declaration.emphasis_3,
+        _ => return Err(format!("unknown theme color {slot}")),
+    })
+}
+
+pub fn parse_color(color_str: &str, colors: &Styling) -> Result<PaletteColor, String> {
+    let color_str = color_str.trim();
+    if let Some(hex) = color_str.strip_prefix('#') {
+        return parse_hex_color(hex).ok_or_else(|| "expected #rgb or #rrggbb".to_string());
+    }
+    if let Some(reference) = color_str.strip_prefix("theme.") {
+        return parse_theme_color(reference, colors);
+    }
+    if let Some(color) = parse_ansi_color_name(color_str) {
+        return Ok(color);
+    }
+    let color_parts = color_str
+        .split(",")
+        .map(|part| part.trim().parse::<u8>().ok())
+        .collect::<Option<Vec<_>>>()
+        .unwrap_or_default();
+    Ok(match color_parts.len() {
+        1 => PaletteColor::EightBit(color_parts[0]),
+        3 => PaletteColor::Rgb((color_parts[0], color_parts[1], color_parts[2])),
+        _ => return Err("expected 8-bit, RGB, hex, named or theme color".to_string()),
+    })
+}
+
+/// Resolves color settings, which besides plain colors can reference other color settings
+/// and use `darken`, `lighten`, `mix` and `alpha`
+pub struct ColorResolver<'a> {
+    pub configuration: &'a BTreeMap<String, String>,
+    pub colors: &'a Styling,
+    // default expression of each color setting
+    pub defaults: &'a dyn Fn(&str) -> Option<String>,
+}
+
+impl ColorResolver<'_> {
+    pub fn resolve(&self, key: &str) -> PaletteColor {
+        self.resolve_key(key, 0).unwrap_or_default()
+    }
+
+    pub fn check(&self, expression: &str) -> Result<(), String> {
+        self.evaluate(expression, 0).map(|_| ())
+    }
+
+    fn resolve_key(&self, key: &str, depth: usize) -> Result<PaletteColor, String> {
+        if depth > MAX_REFERENCE_DEPTH {
+            return Err(format!("{key} {REFERENCE_CYCLE}"));
+        }
+        match self
+            .configuration
+            .get(key)
+            .map(|expression| self.evaluate(expression, depth + 1))
+        {
+            Some(Ok(color)) => return Ok(color),
+            // invalid values fall back to the default, which doesn't help with a cycle
+            Some(Err(error)) if error.ends_with(REFERENCE_CYCLE) => return Err(error),
+            _ => {}
+        }
+        let default = (self.defaults)(key).ok_or_else(|| format!("unknown color {key}"))?;
+        self.evaluate(&default, depth + 1)
+    }
+
+    fn evaluate(&self, expression: &str, depth: usize) -> Result<PaletteColor, String> {
+        let expression = expression.trim();
+        if (self.defaults)(expression).is_some() {
+            return self.resolve_key(expression, depth + 1);
+        }
+        let Some((function, arguments)) = expression
+            .strip_suffix(')')
+            .and_then(|call| call.split_once('('))
+        else {
+            return parse_color(expression, self.colors);
+        };
+        let arguments = split_arguments(arguments);
+        let expected_arguments = match function.trim() {
+            "darken" | "lighten" | "alpha" => 2,
+            "mix" => 3,
+            _ => return Err(format!("unknown function {function}")),
+        };
+        if arguments.len() != expected_arguments {
+            return Err(format!(
+                "{function} takes {expected_arguments} arguments, got {}",
+                arguments.len()
+            ));
+        }
+        let color = self.evaluate(arguments[0], depth + 1)?;
+        Ok(match function.trim() {
+            "darken" => mix(
+                color,
+                PaletteColor::Rgb((0, 0, 0)),
+                1.0 - parse_amount(arguments[1])?,
+            ),
+            "lighten" => mix(
+                color,
+                PaletteColor::Rgb((255, 255, 255)),
+                1.0 - parse_amount(arguments[1])?,
+            ),
+            "alpha" => mix(
+                color,
+                self.resolve_key("BgColor", depth + 1)?,
+                parse_amount(arguments[1])?,
+            ),
+            _ => mix(
+                color,
+                self.evaluate(arguments[1], depth + 1)?,
+                parse_amount(arguments[2])?,
+            ),
+        })
+    }
+}
@@ -0,0 +1,202 @@
+/// Placeholders and conditions a template is allowed to reference
+pub struct TemplateSpec {
+    pub fields: &'static [&'static str],
+    pub conditions: &'static [&'static str],
+    // blocks such as `{right:body}`, which can only be used at the top level
+    pub blocks: &'static [&'static str],
+    // fields that may appear at most once in the whole template
+    pub unique_fields: &'static [&'static str],
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text(String),
+    Field(String),
+    // `{?condition:body}`, or `{?!condition:body}` when negated
+    Conditional {
+        condition: String,
+        negated: bool,
+        body: Vec<Node>,
+    },
+    Block {
+        name: String,
+        body: Vec<Node>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Piece<'a> {
+    Text(&'a str),
+    Field(&'a str),
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+impl Template {
+    pub fn parse(source: &str, spec: &TemplateSpec) -> Result<Self, String> {
+        let mut chars = source.chars().peekable();
+        let template = Self {
+            nodes: parse_nodes(&mut chars, spec, 0)?,
+        };
+        if let Some(field) = spec
+            .unique_fields
+            .iter()
+            .find(|field| template.count_field(field) > 1)
+        {
+            return Err(format!("`{{{field}}}` can only be used once"));
+        }
+        if let Some(block) = spec.blocks.iter().find(|block| {
+            template
+                .nodes
+                .iter()
+                .filter(|node| matches!(node, Node::Block { name, .. } if name == *block))
+                .count()
+                > 1
+        }) {
+            return Err(format!("`{{{block}:…}}` can only be used once"));
+        }
+        Ok(template)
+    }
+
+    fn count_field(&self, field: &str) -> usize {
+        fn walk(nodes: &[Node], field: &str) -> usize {
+            nodes
+                .iter()
+                .map(|node| match node {
+                    Node::Field(name) => (name == field) as usize,
+                    Node::Conditional { body, .. } | Node::Block { body, .. } => walk(body, field),
+                    Node::Text(_) => 0,
+                })
+                .sum()
+        }
+        walk(&self.nodes, field)
+    }
+
+    pub fn contains_field(&self, field: &str) -> bool {
+        self.count_field(field) > 0
+    }
+
+    // the body of the top level block `name`, which is empty if there isn't one
+    pub fn block(&self, name: &str) -> Template {
+        let nodes = self
+            .nodes
+            .iter()
+            .find_map(|node| match node {
+                Node::Block { name: block, body } if block == name => Some(body.clone()),
+                _ => None,
+            })
+            .unwrap_or_default();
+        Template { nodes }
+    }
+
+    // flattens the template into text and fields, keeping only the conditional bodies that hold
+    // and leaving out blocks
+    pub fn pieces(&self, is_true: &dyn Fn(&str) -> bool) -> Vec<Piece<'_>> {
+        fn walk<'a>(nodes: &'a [Node], is_true: &dyn Fn(&str) -> bool, out: &mut Vec<Piece<'a>>) {
+            for node in nodes {
+                match node {
+                    Node::Text(text) => out.push(Piece::Text(text)),
+                    Node::Field(name) => out.push(Piece::Field(name)),
+                    Node::Conditional {
+                        condition,
+                        negated,
+                        body,
+                    } => {
+                        if is_true(condition) != *negated {
+                            walk(body, is_true, out);
+                        }
+                    }
+                    Node::Block { .. } => {}
+                }
+            }
+        }
+        let mut pieces = Vec::new();
+        walk(&self.nodes, is_true, &mut pieces);
+        pieces
+    }
+}
+
+fn parse_nodes(
+    chars: &mut std::iter::Peekable<std::str::Chars>,
+    spec: &TemplateSpec,
+    depth: usize,
+) -> Result<Vec<Node>, String> {
+    let mut nodes = Vec::new();
+    let mut text = String::new();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                text.push('{');
+            }
+            '{' => {
+                if !text.is_empty() {
+                    nodes.push(Node::Text(std::mem::take(&mut text)));
+                }
+                nodes.push(parse_placeholder(chars, spec, depth)?);
+            }
+            '}' if depth > 0 => {
+                if !text.is_empty() {
+                    nodes.push(Node::Text(text));
+                }
+                return Ok(nodes);
+            }
+            _ => text.push(c),
+        }
+    }
+    if depth > 0 {
+        return Err("unclosed `{`".to_string());
+    }
+    if !text.is_empty() {
+        nodes.push(Node::Text(text));
+    }
+    Ok(nodes)
+}
+
+fn parse_placeholder(
+    chars: &mut std::iter::Peekable<std::str::Chars>,
+    spec: &TemplateSpec,
+    depth: usize,
+) -> Result<Node, String> {
+    let mut name = String::new();
+    loop {
+        match chars.next() {
+            Some('}') => {
+                if !spec.fields.contains(&name.as_str()) {
+                    return Err(format!("unknown placeholder `{{{name}}}`"));
+                }
+                return Ok(Node::Field(name));
+            }
+            Some(':') => break,
+            Some(c) => name.push(c),
+            None => return Err("unclosed `{`".to_string()),
+        }
+    }
+    let Some(condition) = name.strip_prefix('?') else {
+        if !spec.blocks.contains(&name.as_str()) {
+            return Err(format!("unknown block `{{{name}:…}}`"));
+        }
+        if depth > 0 {
+            return Err(format!("`{{{name}:…}}` can't be nested"));
+        }
+        return Ok(Node::Block {
+            body: parse_nodes(chars, spec, depth + 1)?,
+            name,
+        });
+    };
+    let (condition, negated) = match condition.strip_prefix('!') {
+        Some(condition) => (condition, true),
+        None => (condition, false),
+    };
+    if !spec.conditions.contains(&condition) {
+        return Err(format!("unknown condition `{condition}`"));
+    }
+    Ok(Node::Conditional {
+        condition: condition.to_string(),
+        negated,
+        body: parse_nodes(chars, spec, depth + 1)?,
+    })
+}
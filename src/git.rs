@@ -0,0 +1,40 @@
+/// State of the git repository of a directory, from `git status --porcelain=v2 --branch`
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct GitStatus {
+    pub branch: String,
+    pub ahead: usize,
+    pub behind: usize,
+    pub dirty: bool,
+}
+
+impl GitStatus {
+    pub fn parse(output: &str) -> Self {
+        let mut status = Self::default();
+        let mut commit = "";
+        for line in output.lines() {
+            let Some(header) = line.strip_prefix("# ") else {
+                // every other line is a changed or untracked file
+                status.dirty = true;
+                continue;
+            };
+            match header.split_once(' ') {
+                Some(("branch.oid", oid)) => commit = oid,
+                Some(("branch.head", head)) => status.branch = head.to_string(),
+                Some(("branch.ab", ahead_behind)) => {
+                    for count in ahead_behind.split_whitespace() {
+                        if let Some(ahead) = count.strip_prefix('+') {
+                            status.ahead = ahead.parse().unwrap_or_default();
+                        } else if let Some(behind) = count.strip_prefix('-') {
+                            status.behind = behind.parse().unwrap_or_default();
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        if status.branch == "(detached)" {
+            status.branch = commit.chars().take(7).collect();
+        }
+        status
+    }
+}
@@ -1,7 +1,15 @@
-use unicode_width::UnicodeWidthStr;
+use std::cmp::Ordering;
 
+use crate::attributes::TextAttributes;
+
+use crate::format::{Piece, Template, TemplateSpec};
+use crate::git::GitStatus;
+use crate::tab::TabLabel;
+use crate::text::display_width;
 use crate::LinePart;
+use crate::LinePartKind;
 use crate::UserConfiguration;
+use regex_lite::Regex;
 use zellij_tile::prelude::*;
 use zellij_tile_utils::style;
 
@@ -95,6 +103,70 @@ fn populate_tabs_in_tab_line(
     }
 }
 
+// fills the bar with the tabs from `first_visible_tab` onwards, moving it only as far as needed
+// for the active tab to be visible
+fn scroll_tabs_in_tab_line(
+    tabs_before_active: Vec<LinePart>,
+    active_tab: LinePart,
+    tabs_after_active: Vec<LinePart>,
+    first_visible_tab: usize,
+    cols: usize,
+    user_conf: &UserConfiguration,
+) -> Vec<LinePart> {
+    let active_tab_index = tabs_before_active.len();
+    let mut all_tabs = tabs_before_active;
+    all_tabs.push(active_tab);
+    all_tabs.extend(tabs_after_active);
+    // the tabs that fit starting from `start`, as many as possible
+    let visible_tabs = |start: usize| {
+        let collapsed_left_len =
+            left_more_message(start, user_conf.clone(), start.saturating_sub(1)).len;
+        let mut width = collapsed_left_len;
+        let mut end = start;
+        while end < all_tabs.len() {
+            let right_count = all_tabs.len() - end - 1;
+            let collapsed_right_len =
+                right_more_message(right_count, user_conf.clone(), end + 1).len;
+            if width + all_tabs[end].len + collapsed_right_len > cols {
+                break;
+            }
+            width += all_tabs[end].len;
+            end += 1;
+        }
+        start..end
+    };
+    let mut start = first_visible_tab.min(active_tab_index);
+    while start < active_tab_index && !visible_tabs(start).contains(&active_tab_index) {
+        start += 1;
+    }
+    // moves back over the tabs to the left when there's room for them after the last tab
+    let shows_the_rest = |start: usize| {
+        let visible = visible_tabs(start);
+        visible.contains(&active_tab_index) && visible.end == all_tabs.len()
+    };
+    while start > 0 && shows_the_rest(start - 1) {
+        start -= 1;
+    }
+    let visible = visible_tabs(start);
+    if !visible.contains(&active_tab_index) {
+        // the active tab only fits without the markers
+        return vec![all_tabs.swap_remove(active_tab_index)];
+    }
+    let right_count = all_tabs.len() - visible.end;
+    let mut tabs_to_render = vec![left_more_message(
+        visible.start,
+        user_conf.clone(),
+        visible.start.saturating_sub(1),
+    )];
+    tabs_to_render.extend(all_tabs.drain(visible.clone()));
+    tabs_to_render.push(right_more_message(
+        right_count,
+        user_conf.clone(),
+        visible.end,
+    ));
+    tabs_to_render
+}
+
 fn left_more_message(
     tab_count_to_the_left: usize,
     user_conf: UserConfiguration,
@@ -108,14 +180,18 @@ fn left_more_message(
     } else {
         " ← +many ".to_string()
     };
-    let more_text_len = more_text.len();
-    let more_styled_text = style!(user_conf.color_fg, user_conf.color_others)
-        .bold()
+    let more_text_len = display_width(&more_text);
+    let more_styled_text = user_conf
+        .others_attributes
+        .apply(style!(user_conf.color_fg, user_conf.color_others))
         .paint(more_text);
     LinePart {
         part: more_styled_text.to_string(),
         len: more_text_len,
         tab_index: Some(tab_index),
+        kind: LinePartKind::CollapsedTabs,
+        bg: user_conf.color_others,
+        failed_pane_marker: None,
     }
 }
 
@@ -132,110 +208,290 @@ fn right_more_message(
     } else {
         " +many → ".to_string()
     };
-    let more_text_len = more_text.len();
-    let more_styled_text = style!(user_conf.color_fg, user_conf.color_others)
-        .bold()
+    let more_text_len = display_width(&more_text);
+    let more_styled_text = user_conf
+        .others_attributes
+        .apply(style!(user_conf.color_fg, user_conf.color_others))
         .paint(more_text);
     LinePart {
         part: more_styled_text.to_string(),
         len: more_text_len,
         tab_index: Some(tab_index),
+        kind: LinePartKind::CollapsedTabs,
+        bg: user_conf.color_others,
+        failed_pane_marker: None,
     }
 }
 
-fn tab_line_prefix(
-    session_name: String,
-    mode: InputMode,
-    user_conf: UserConfiguration,
-    cols: usize,
-    session_directory: String,
-) -> Vec<LinePart> {
-    let mut parts: Vec<LinePart> = Vec::new();
+pub const BAR_TEMPLATE: TemplateSpec = TemplateSpec {
+    fields: &[
+        "session_dir",
+        "session_sep",
+        "session",
+        "mode",
+        "tabs",
+        "sep",
+        "clock",
+        "git",
+    ],
+    conditions: &[
+        "session_dir",
+        "mode",
+        "sync",
+        "fullscreen",
+        "floating",
+        "git",
+    ],
+    blocks: &["center", "right"],
+    unique_fields: &["tabs"],
+};
 
-    let bg_color = user_conf.color_bg;
-    let normal_mode_color = user_conf.color_normal_mode;
-    let other_modes_color = user_conf.color_other_modes;
+pub const DEFAULT_BAR_FORMAT: &str =
+    "{?session_dir:{session_dir}{session_sep}}{session} {mode} {tabs}";
 
-    let session_name_separator = "-";
+/// What the bar displays besides the tabs
+pub struct BarContext<'a> {
+    pub session_name: String,
+    pub session_directory: String,
+    pub mode: InputMode,
+    pub active_tab: &'a TabInfo,
+    pub clock: String,
+    // `None` outside git repositories
+    pub git: Option<GitStatus>,
+    pub first_visible_tab: usize,
+    // width of the longest tab name, where shortening the names of the inactive tabs starts
+    pub longest_tab_name: usize,
+}
+
+// the regular expression of SessionNamePattern, which may only have the groups `dir` and `name`
+pub fn parse_session_name_pattern(pattern: &str) -> Result<Regex, String> {
+    let regex = Regex::new(pattern).map_err(|error| error.to_string())?;
+    if let Some(group) = regex
+        .capture_names()
+        .flatten()
+        .find(|group| !["dir", "name"].contains(group))
+    {
+        return Err(format!("unknown group {group}, expected dir or name"));
+    }
+    Ok(regex)
+}
+
+// splits the session name into the text displayed with the session directory style (if any),
+// the separator in between and the text displayed with the session name style
+fn split_session_name(
+    session_name: String,
+    session_directory: String,
+    user_conf: &UserConfiguration,
+) -> (Option<String>, String, String) {
+    let session_name_separator = user_conf.session_name_separator.as_str();
+    if user_conf.display_session_directory {
+        return (
+            Some(session_directory),
+            session_name_separator.to_string(),
+            session_name,
+        );
+    }
+    if let Some(captures) = user_conf
+        .session_name_pattern
+        .as_ref()
+        .and_then(|pattern| pattern.captures(&session_name))
+    {
+        let (dir, name) = (captures.name("dir"), captures.name("name"));
+        // whatever the pattern matched in between the directory and the name
+        let separator = match (dir, name) {
+            (Some(dir), Some(name)) if dir.end() <= name.start() => {
+                session_name[dir.end()..name.start()].to_string()
+            }
+            _ => String::new(),
+        };
+        return (
+            dir.map(|dir| dir.as_str().to_string()),
+            separator,
+            name.map_or(session_name.clone(), |name| name.as_str().to_string()),
+        );
+    }
+    let name_parts_len = user_conf.session_name_parts;
+    if session_name_separator.is_empty() || name_parts_len == 0 {
+        return (None, session_name_separator.to_string(), session_name);
+    }
     let session_name_parts = session_name
         .split(session_name_separator)
         .collect::<Vec<_>>();
     let session_name_parts_len = session_name_parts.len();
-    let mut prefix_text_len = 0;
+    if session_name_parts_len <= name_parts_len {
+        return (None, session_name_separator.to_string(), session_name);
+    }
+    (
+        Some(
+            session_name_parts[..session_name_parts_len - name_parts_len]
+                .join(session_name_separator),
+        ),
+        session_name_separator.to_string(),
+        session_name_parts[session_name_parts_len - name_parts_len..].join(session_name_separator),
+    )
+}
+
+fn styled_part(
+    text: String,
+    attributes: TextAttributes,
+    fg: PaletteColor,
+    bg: PaletteColor,
+) -> LinePart {
+    let len = display_width(&text);
+    LinePart {
+        part: attributes.apply(style!(fg, bg)).paint(text).to_string(),
+        len,
+        tab_index: None,
+        kind: LinePartKind::Other,
+        bg,
+        failed_pane_marker: None,
+    }
+}
 
-    let has_prefix = user_conf.display_session_directory || session_name_parts_len > 2;
+fn separator_part(separator: &str) -> Option<LinePart> {
+    if separator.is_empty() {
+        return None;
+    }
+    Some(LinePart {
+        part: separator.to_string(),
+        len: display_width(separator),
+        tab_index: None,
+        kind: LinePartKind::Separator,
+        bg: PaletteColor::default(),
+        failed_pane_marker: None,
+    })
+}
+
+// powerline glyphs pointing left, e.g. `\u{e0b2}`, which go from the background on their right
+// to the background on their left
+const LEFT_POINTING_GLYPHS: [char; 8] = [
+    '\u{e0b2}', '\u{e0b3}', '\u{e0b6}', '\u{e0b7}', '\u{e0ba}', '\u{e0bb}', '\u{e0be}', '\u{e0bf}',
+];
 
-    if has_prefix {
-        let prefix_text = if user_conf.display_session_directory {
-            session_directory
+// powerline separators go from the background of the part on their left to the background of
+// the part on their right
+fn paint_separators(line: &mut [LinePart], user_conf: &UserConfiguration) {
+    let is_segment = |part: &&LinePart| part.len > 0 && part.kind != LinePartKind::Separator;
+    for i in 0..line.len() {
+        if line[i].kind != LinePartKind::Separator {
+            continue;
+        }
+        let left_bg = line[..i]
+            .iter()
+            .rev()
+            .find(is_segment)
+            .map_or(user_conf.color_bg, |part| part.bg);
+        let right_bg = line[i + 1..]
+            .iter()
+            .find(is_segment)
+            .map_or(user_conf.color_bg, |part| part.bg);
+        let points_left = line[i]
+            .part
+            .chars()
+            .any(|glyph| LEFT_POINTING_GLYPHS.contains(&glyph));
+        let (fg, bg) = if user_conf.powerline_separators && points_left {
+            (right_bg, left_bg)
+        } else if user_conf.powerline_separators {
+            (left_bg, right_bg)
         } else {
-            session_name_parts[..session_name_parts_len - 2].join(session_name_separator)
+            (user_conf.color_separator, user_conf.color_bg)
         };
-        prefix_text_len = prefix_text.chars().count();
-        let text_color = user_conf.color_session_directory;
-
-        let prefix_styled_text = style!(text_color, bg_color).bold().paint(prefix_text);
-        parts.push(LinePart {
-            part: prefix_styled_text.to_string(),
-            len: prefix_text_len,
-            tab_index: None,
-        });
+        line[i].part = style!(fg, bg).paint(line[i].part.clone()).to_string();
+        line[i].bg = bg;
     }
+}
 
-    let name_part = format!(
-        "{}{} ",
-        if has_prefix {
-            session_name_separator
-        } else {
-            ""
-        },
-        if user_conf.display_session_directory || session_name_parts_len == 1 {
-            session_name
-        } else {
-            session_name_parts[session_name_parts_len - 2..session_name_parts_len]
-                .join(session_name_separator)
+// each tab carries the separators on its side facing the active tab, so that they are only
+// shown along with it
+fn tab_separators(
+    position: usize,
+    active_tab_index: usize,
+    user_conf: &UserConfiguration,
+) -> (&str, &str) {
+    let active_left = user_conf.active_tab_left_separator.as_str();
+    let active_right = user_conf.active_tab_right_separator.as_str();
+    let tab_separator = user_conf.tab_separator.as_str();
+    match position.cmp(&active_tab_index) {
+        Ordering::Less if position + 1 == active_tab_index && !active_left.is_empty() => ("", ""),
+        Ordering::Less => ("", tab_separator),
+        Ordering::Equal => (active_left, active_right),
+        Ordering::Greater if position == active_tab_index + 1 && !active_right.is_empty() => {
+            ("", "")
         }
-    );
-    let name_part_len = name_part.width();
-    let text_color = user_conf.color_session_name;
-    let name_part_styled_text = style!(text_color, bg_color)
-        .bold()
-        .italic()
-        .paint(name_part);
-    if cols.saturating_sub(prefix_text_len) >= name_part_len {
-        parts.push(LinePart {
-            part: name_part_styled_text.to_string(),
-            len: name_part_len,
-            tab_index: None,
-        })
+        Ordering::Greater => (tab_separator, ""),
     }
+}
 
-    let mut mode_part = user_conf.mode_display.get(&mode).unwrap().to_owned();
-    mode_part.push(' ');
-    let mode_part_len = mode_part.width();
-    let mode_part_styled_text = match mode {
-        InputMode::Normal => style!(normal_mode_color, bg_color).bold().paint(mode_part),
-        _ => style!(other_modes_color, bg_color).bold().paint(mode_part),
-    };
-    if cols.saturating_sub(prefix_text_len) >= mode_part_len {
-        parts.push(LinePart {
-            part: mode_part_styled_text.to_string(),
-            len: mode_part_len,
-            tab_index: None,
+/// Renders every tab, displaying the given label for the inactive ones
+pub type RenderTabs<'a> = dyn Fn(TabLabel) -> Vec<LinePart> + 'a;
+
+/// What happens to the tabs that don't fit in the bar
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum OverflowMode {
+    // the tabs around the active one are displayed with markers for how many are left out
+    #[default]
+    Collapse,
+    // inactive tabs only display their index, and are collapsed if that's still not enough
+    Compress,
+    // like collapse, but the visible tabs only move when the active tab leaves them
+    Scroll,
+    // only the active tab is displayed along with the markers
+    ActiveOnly,
+}
+
+impl OverflowMode {
+    pub fn parse(mode: &str) -> Result<Self, String> {
+        Ok(match mode.trim() {
+            "collapse" => Self::Collapse,
+            "compress" => Self::Compress,
+            "scroll" => Self::Scroll,
+            "active-only" => Self::ActiveOnly,
+            _ => return Err("expected collapse, compress, scroll or active-only".to_string()),
         })
     }
-    parts
 }
 
-pub fn tab_line(
-    session_name: String,
-    mut all_tabs: Vec<LinePart>,
+// names of inactive tabs aren't shortened any further than this when making room for more tabs
+const MIN_ADAPTIVE_TAB_NAME_WIDTH: usize = 4;
+
+fn tabs_in_tab_line(
+    render_tabs: &RenderTabs,
     active_tab_index: usize,
+    first_visible_tab: usize,
+    longest_tab_name: usize,
     cols: usize,
     user_conf: UserConfiguration,
-    mode: InputMode,
-    session_directory: String,
-) -> Vec<LinePart> {
+) -> (Vec<LinePart>, usize) {
+    let render_tabs_with_separators = |label| {
+        let mut all_tabs = render_tabs(label);
+        for tab in all_tabs.iter_mut() {
+            let (left, right) =
+                tab_separators(tab.tab_index.unwrap(), active_tab_index, &user_conf);
+            tab.len += display_width(left) + display_width(right);
+        }
+        all_tabs
+    };
+    let mut all_tabs = render_tabs_with_separators(TabLabel::Full);
+    if user_conf.adaptive_tab_names && get_current_title_len(&all_tabs) > cols {
+        // shortens the names of the inactive tabs before any of them gets collapsed, searching
+        // for the widest limit at which they all fit
+        let mut shortest = MIN_ADAPTIVE_TAB_NAME_WIDTH;
+        let mut longest = longest_tab_name.max(shortest);
+        while shortest < longest {
+            let limit = (shortest + longest).div_ceil(2);
+            let tabs = render_tabs_with_separators(TabLabel::ShortenedName(limit));
+            if get_current_title_len(&tabs) <= cols {
+                shortest = limit;
+            } else {
+                longest = limit - 1;
+            }
+        }
+        all_tabs = render_tabs_with_separators(TabLabel::ShortenedName(shortest));
+    }
+    let overflows = get_current_title_len(&all_tabs) > cols;
+    if user_conf.overflow_mode == OverflowMode::Compress && overflows {
+        all_tabs = render_tabs_with_separators(TabLabel::IndexOnly);
+    }
     let mut tabs_after_active = all_tabs.split_off(active_tab_index);
     let mut tabs_before_active = all_tabs;
     let active_tab = if !tabs_after_active.is_empty() {
@@ -243,30 +499,324 @@ pub fn tab_line(
     } else {
         tabs_before_active.pop().unwrap()
     };
-    let mut prefix = tab_line_prefix(
-        session_name,
-        mode,
-        user_conf.clone(),
-        cols,
-        session_directory,
-    );
-    let prefix_len = get_current_title_len(&prefix);
 
     // if active tab alone won't fit in cols, don't draw any tabs
-    if prefix_len + active_tab.len > cols {
-        return prefix;
+    if active_tab.len > cols {
+        return (Vec::new(), first_visible_tab);
     }
 
     let mut tabs_to_render = vec![active_tab];
 
-    populate_tabs_in_tab_line(
-        &mut tabs_before_active,
-        &mut tabs_after_active,
-        &mut tabs_to_render,
-        cols.saturating_sub(prefix_len),
-        user_conf,
-    );
-    prefix.append(&mut tabs_to_render);
+    match user_conf.overflow_mode {
+        OverflowMode::Scroll => {
+            tabs_to_render = scroll_tabs_in_tab_line(
+                tabs_before_active,
+                tabs_to_render.remove(0),
+                tabs_after_active,
+                if overflows { first_visible_tab } else { 0 },
+                cols,
+                &user_conf,
+            );
+        }
+        OverflowMode::ActiveOnly if overflows => {
+            let left_count = tabs_before_active.len();
+            let right_count = tabs_after_active.len();
+            let collapsed_left =
+                left_more_message(left_count, user_conf.clone(), left_count.saturating_sub(1));
+            let collapsed_right =
+                right_more_message(right_count, user_conf.clone(), left_count + 1);
+            if get_current_title_len(&tabs_to_render) + collapsed_left.len + collapsed_right.len
+                <= cols
+            {
+                tabs_to_render.insert(0, collapsed_left);
+                tabs_to_render.push(collapsed_right);
+            }
+        }
+        _ => populate_tabs_in_tab_line(
+            &mut tabs_before_active,
+            &mut tabs_after_active,
+            &mut tabs_to_render,
+            cols,
+            user_conf.clone(),
+        ),
+    }
+
+    // the window starts at the leftmost tab that made it into the bar
+    let window_start = tabs_to_render
+        .iter()
+        .filter(|tab| tab.kind == LinePartKind::Tab)
+        .find_map(|tab| tab.tab_index)
+        .unwrap_or(first_visible_tab);
+    let mut tabs_with_separators = Vec::new();
+    for mut tab in tabs_to_render {
+        if tab.kind != LinePartKind::Tab {
+            tabs_with_separators.push(tab);
+            continue;
+        }
+        let (left, right) = tab_separators(tab.tab_index.unwrap(), active_tab_index, &user_conf);
+        tab.len -= display_width(left) + display_width(right);
+        tabs_with_separators.extend(separator_part(left));
+        tabs_with_separators.push(tab);
+        tabs_with_separators.extend(separator_part(right));
+    }
+    (tabs_with_separators, window_start)
+}
 
-    prefix
+// keeps the parts in order as long as they fit in the remaining cols, returns them along with
+// where the tabs go
+fn fit_parts(
+    parts: Vec<LinePart>,
+    tabs_position: Option<usize>,
+    remaining_cols: &mut usize,
+) -> (Vec<LinePart>, Option<usize>) {
+    let mut fitting_parts = Vec::new();
+    let mut tabs_insert_index = 0;
+    for (i, part) in parts.into_iter().enumerate() {
+        if part.len > *remaining_cols {
+            continue;
+        }
+        *remaining_cols -= part.len;
+        if tabs_position.is_some_and(|position| i < position) {
+            tabs_insert_index += 1;
+        }
+        fitting_parts.push(part);
+    }
+    (fitting_parts, tabs_position.map(|_| tabs_insert_index))
+}
+
+fn spacer_part(cols: usize, user_conf: &UserConfiguration) -> LinePart {
+    styled_part(
+        " ".repeat(cols),
+        TextAttributes::default(),
+        user_conf.color_fg,
+        user_conf.color_bg,
+    )
+}
+
+pub fn tab_line(
+    context: BarContext,
+    render_tabs: &RenderTabs,
+    cols: usize,
+    user_conf: UserConfiguration,
+) -> (Vec<LinePart>, usize) {
+    let BarContext {
+        session_name,
+        session_directory,
+        mode,
+        active_tab,
+        clock,
+        git,
+        first_visible_tab,
+        longest_tab_name,
+    } = context;
+    let (session_prefix, session_separator, session_name) =
+        split_session_name(session_name, session_directory, &user_conf);
+    let mode_label = user_conf.mode_display.get(&mode).unwrap().to_owned();
+    let is_true = |condition: &str| match condition {
+        "session_dir" => session_prefix
+            .as_ref()
+            .is_some_and(|prefix| !prefix.is_empty()),
+        "mode" => !mode_label.is_empty(),
+        "sync" => active_tab.is_sync_panes_active,
+        "fullscreen" => active_tab.is_fullscreen_active,
+        "floating" => active_tab.are_floating_panes_visible,
+        "git" => git.is_some(),
+        _ => false,
+    };
+
+    let bg_color = user_conf.color_bg;
+    let text_attributes = TextAttributes {
+        bold: true,
+        ..Default::default()
+    };
+    let (mode_color, mode_bg_color) = user_conf.mode_colors[&mode];
+    // returns the parts of a zone of the bar along with where the tabs go, if they're in it
+    let zone_parts = |template: &Template| {
+        let mut parts: Vec<LinePart> = Vec::new();
+        let mut tabs_position = None;
+        // text in between placeholders takes the style of the placeholder that follows it
+        let mut pending_text = String::new();
+        for piece in template.pieces(&is_true) {
+            let (value, attributes, fg, bg) = match piece {
+                Piece::Text(text) => {
+                    pending_text.push_str(text);
+                    continue;
+                }
+                Piece::Field(name @ ("tabs" | "sep")) => {
+                    if !pending_text.is_empty() {
+                        parts.push(styled_part(
+                            std::mem::take(&mut pending_text),
+                            text_attributes,
+                            user_conf.color_fg,
+                            bg_color,
+                        ));
+                    }
+                    if name == "tabs" {
+                        tabs_position = Some(parts.len());
+                    } else {
+                        parts.extend(separator_part(&user_conf.segment_separator));
+                    }
+                    continue;
+                }
+                Piece::Field("git") => {
+                    let Some(git) = &git else {
+                        continue;
+                    };
+                    let mut git_parts = vec![(git.branch.clone(), user_conf.color_git_branch)];
+                    let mut ahead_behind = String::new();
+                    if git.ahead > 0 {
+                        ahead_behind += &format!("↑{}", git.ahead);
+                    }
+                    if git.behind > 0 {
+                        ahead_behind += &format!("↓{}", git.behind);
+                    }
+                    if !ahead_behind.is_empty() {
+                        git_parts
+                            .push((format!(" {ahead_behind}"), user_conf.color_git_ahead_behind));
+                    }
+                    if git.dirty && !user_conf.git_dirty_indicator.is_empty() {
+                        git_parts.push((
+                            format!(" {}", user_conf.git_dirty_indicator),
+                            user_conf.color_git_dirty,
+                        ));
+                    }
+                    git_parts[0]
+                        .0
+                        .insert_str(0, &std::mem::take(&mut pending_text));
+                    for (text, fg) in git_parts {
+                        parts.push(styled_part(
+                            text,
+                            user_conf.git_attributes,
+                            fg,
+                            user_conf.color_git_bg,
+                        ));
+                    }
+                    continue;
+                }
+                Piece::Field("session_dir") => (
+                    session_prefix.clone().unwrap_or_default(),
+                    user_conf.session_directory_attributes,
+                    user_conf.color_session_directory,
+                    user_conf.color_session_directory_bg,
+                ),
+                Piece::Field("session_sep") => (
+                    session_separator.clone(),
+                    user_conf.session_directory_attributes,
+                    user_conf.color_session_directory,
+                    user_conf.color_session_directory_bg,
+                ),
+                Piece::Field("session") => (
+                    session_name.clone(),
+                    user_conf.session_name_attributes,
+                    user_conf.color_session_name,
+                    user_conf.color_session_name_bg,
+                ),
+                Piece::Field("mode") => (
+                    mode_label.clone(),
+                    user_conf.mode_attributes,
+                    mode_color,
+                    mode_bg_color,
+                ),
+                Piece::Field("clock") => (
+                    clock.clone(),
+                    user_conf.clock_attributes,
+                    user_conf.color_clock,
+                    user_conf.color_clock_bg,
+                ),
+                Piece::Field(_) => (String::new(), text_attributes, user_conf.color_fg, bg_color),
+            };
+            let text = std::mem::take(&mut pending_text) + &value;
+            if !text.is_empty() {
+                let mut part = styled_part(text, attributes, fg, bg);
+                part.kind = match piece {
+                    Piece::Field("session_dir" | "session_sep" | "session") => {
+                        LinePartKind::Session
+                    }
+                    Piece::Field("mode") => LinePartKind::Mode,
+                    _ => LinePartKind::Other,
+                };
+                parts.push(part);
+            }
+        }
+        if !pending_text.is_empty() {
+            parts.push(styled_part(
+                pending_text,
+                text_attributes,
+                user_conf.color_fg,
+                bg_color,
+            ));
+        }
+        (parts, tabs_position)
+    };
+
+    let (mut left_parts, mut left_tabs_position) = zone_parts(&user_conf.bar_format);
+    let config_error_count = user_conf.config_errors.len();
+    if config_error_count > 0 {
+        let mut config_errors_part = styled_part(
+            format!(
+                "⚠ {config_error_count} config error{} ",
+                if config_error_count == 1 { "" } else { "s" }
+            ),
+            text_attributes,
+            user_conf.color_config_errors,
+            bg_color,
+        );
+        config_errors_part.kind = LinePartKind::ConfigErrors;
+        left_parts.insert(0, config_errors_part);
+        left_tabs_position = left_tabs_position.map(|position| position + 1);
+    }
+    let (center_parts, center_tabs_position) = zone_parts(&user_conf.bar_format.block("center"));
+    let (right_parts, right_tabs_position) = zone_parts(&user_conf.bar_format.block("right"));
+
+    // the zones are filled in order of priority, the tabs get whatever width is left
+    let mut remaining_cols = cols;
+    let (mut left, left_tabs_position) =
+        fit_parts(left_parts, left_tabs_position, &mut remaining_cols);
+    let (mut right, right_tabs_position) =
+        fit_parts(right_parts, right_tabs_position, &mut remaining_cols);
+    let (mut center, center_tabs_position) =
+        fit_parts(center_parts, center_tabs_position, &mut remaining_cols);
+    let tabs_zone = [
+        (&mut left, left_tabs_position),
+        (&mut center, center_tabs_position),
+        (&mut right, right_tabs_position),
+    ]
+    .into_iter()
+    .find_map(|(zone, position)| Some((zone, position?)));
+    let mut window_start = first_visible_tab;
+    if let Some((zone, tabs_insert_index)) = tabs_zone {
+        let tabs;
+        (tabs, window_start) = tabs_in_tab_line(
+            render_tabs,
+            active_tab.position,
+            first_visible_tab,
+            longest_tab_name,
+            remaining_cols,
+            user_conf.clone(),
+        );
+        zone.splice(tabs_insert_index..tabs_insert_index, tabs);
+    }
+
+    let mut line = left;
+    if !center.is_empty() || !right.is_empty() {
+        let left_len = get_current_title_len(&line);
+        let center_len = get_current_title_len(&center);
+        let right_len = get_current_title_len(&right);
+        // the center zone is centered in the whole bar unless that overlaps the other zones
+        let center_start = (cols.saturating_sub(center_len) / 2)
+            .max(left_len)
+            .min(cols.saturating_sub(right_len + center_len));
+        line.push(spacer_part(
+            center_start.saturating_sub(left_len),
+            &user_conf,
+        ));
+        line.append(&mut center);
+        line.push(spacer_part(
+            cols.saturating_sub(center_start + center_len + right_len),
+            &user_conf,
+        ));
+        line.append(&mut right);
+    }
+    paint_separators(&mut line, &user_conf);
+    (line, window_start)
 }
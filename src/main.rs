@@ -1,32 +1,102 @@
+mod attributes;
+mod color;
+mod format;
+mod git;
 mod line;
+mod mouse;
+mod path;
+mod picker;
 mod tab;
+mod text;
 
 use std::cmp::{max, min};
-use std::collections::{BTreeMap, HashMap};
-use std::convert::TryInto;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::Range;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use tab::get_tab_to_focus;
+use regex_lite::Regex;
+
+use tab::{get_clicked_failed_pane, get_clicked_line_part};
 use zellij_tile::prelude::*;
 
-use crate::line::tab_line;
-use crate::tab::tab_style;
+use crate::attributes::TextAttributes;
+use crate::color::{mix, parse_amount, ColorResolver};
+use crate::format::{Template, TemplateSpec};
+use crate::git::GitStatus;
+use crate::line::{
+    parse_session_name_pattern, tab_line, BarContext, OverflowMode, BAR_TEMPLATE,
+    DEFAULT_BAR_FORMAT,
+};
+use crate::mouse::{MouseAction, MouseEvent, MouseTarget, MOUSE_EVENTS, MOUSE_TARGETS};
+use crate::path::{abbreviate_home, shorten, DirectoryFormat};
+use crate::picker::Picker;
+use crate::tab::{
+    failed_command_panes, focused_pane, is_default_tab_name, tab_name_width, tab_style, Indicator,
+    TabContext, TabLabel, DEFAULT_TAB_FORMAT, INDEX_ONLY_TAB_FORMAT, TAB_TEMPLATE,
+};
+use crate::text::{truncate_middle, Ellipsis};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum LinePartKind {
+    #[default]
+    Other,
+    Tab,
+    CollapsedTabs,
+    // the session name or directory
+    Session,
+    Mode,
+    // painted once its neighbours are known, until then `part` holds the bare separator
+    Separator,
+    ConfigErrors,
+}
 
 #[derive(Debug, Default)]
 pub struct LinePart {
     part: String,
     len: usize,
     tab_index: Option<usize>,
+    kind: LinePartKind,
+    bg: PaletteColor,
+    // columns of the failed command marker within the part and the pane it stands for
+    failed_pane_marker: Option<(Range<usize>, u32)>,
 }
 
 #[derive(Default)]
 struct State {
     tabs: Vec<TabInfo>,
+    panes: PaneManifest,
+    // failed command panes that were already seen by visiting their tab
+    seen_failed_panes: HashSet<u32>,
+    pane_cwds: HashMap<PaneId, PathBuf>,
+    home: String,
+    // last two names given to each automatically renamed tab by id, since a tab update can
+    // arrive before the latest rename is applied
+    auto_tab_names: HashMap<usize, (String, String)>,
     active_tab_idx: usize,
     configuration: BTreeMap<String, String>,
     user_configuration: UserConfiguration,
     mode_info: ModeInfo,
     tab_line: Vec<LinePart>,
+    // column the mouse was last over, which is what scrolling applies to
+    hover_col: Option<usize>,
+    // set when this instance is a tab picker instead of the bar
+    picker: Option<Picker>,
+    // position of the leftmost tab in the bar, which the viewport of the scroll overflow mode
+    // starts from
+    first_visible_tab: usize,
+    // full path of the directory the session was started in
     session_directory: String,
+    // whether the session directory command exited with an error or printed nothing
+    session_directory_failed: bool,
+    git_root: Option<String>,
+    can_run_commands: bool,
+    clock: String,
+    refresh_scheduled: bool,
+    git: Option<GitStatus>,
+    // directory of the last git status, which is the session's one when it's `None`
+    git_directory: Option<PathBuf>,
+    next_git_refresh: f64,
 }
 
 register_plugin!(State);
@@ -37,42 +107,167 @@ pub struct UserConfiguration {
     color_bg: PaletteColor,
     color_session_directory: PaletteColor,
     color_session_name: PaletteColor,
+    color_session_directory_bg: PaletteColor,
+    color_session_name_bg: PaletteColor,
+    color_tab_bg: PaletteColor,
+    color_active_tab_bg: PaletteColor,
+    color_separator: PaletteColor,
+    color_clock: PaletteColor,
+    color_clock_bg: PaletteColor,
     color_tab: PaletteColor,
     color_active_tab: PaletteColor,
-    color_normal_mode: PaletteColor,
-    color_other_modes: PaletteColor,
     color_others: PaletteColor,
     display_session_directory: bool,
     default_tab_name: String,
     mode_display: HashMap<InputMode, String>,
+    // foreground and background color of each mode
+    mode_colors: HashMap<InputMode, (PaletteColor, PaletteColor)>,
+    bar_format: Template,
+    tab_format: Template,
+    active_tab_format: Template,
+    index_only_tab_format: Template,
+    color_config_errors: PaletteColor,
+    config_errors: Vec<ConfigError>,
+    mode_theming: bool,
+    mode_tint_strength: f64,
+    session_directory_attributes: TextAttributes,
+    session_name_attributes: TextAttributes,
+    mode_attributes: TextAttributes,
+    tab_attributes: TextAttributes,
+    active_tab_attributes: TextAttributes,
+    others_attributes: TextAttributes,
+    segment_separator: String,
+    tab_separator: String,
+    active_tab_left_separator: String,
+    active_tab_right_separator: String,
+    powerline_separators: bool,
+    clock_format: String,
+    clock_attributes: TextAttributes,
+    sync_indicator: Indicator,
+    fullscreen_indicator: Indicator,
+    floating_indicator: Indicator,
+    other_clients_indicator: Indicator,
+    swap_layout_dirty_indicator: Indicator,
+    failed_command_indicator: Indicator,
+    pane_title_max_width: usize,
+    auto_rename_tabs: bool,
+    session_directory_from_active_tab: bool,
+    abbreviate_home: bool,
+    shorten_paths: bool,
+    session_directory_format: DirectoryFormat,
+    session_directory_max_width: usize,
+    session_directory_placeholder: String,
+    color_git_branch: PaletteColor,
+    color_git_ahead_behind: PaletteColor,
+    color_git_dirty: PaletteColor,
+    color_git_bg: PaletteColor,
+    git_attributes: TextAttributes,
+    git_dirty_indicator: String,
+    git_refresh_interval: usize,
+    session_name_separator: String,
+    session_name_parts: usize,
+    session_name_pattern: Option<Regex>,
+    max_tab_name_width: usize,
+    active_max_tab_name_width: usize,
+    tab_name_ellipsis: Ellipsis,
+    adaptive_tab_names: bool,
+    overflow_mode: OverflowMode,
+    mouse_bindings: HashMap<(MouseTarget, MouseEvent), MouseAction>,
+    invert_scroll: bool,
+    wrap_around: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct ConfigError {
+    key: String,
+    value: String,
+    reason: String,
 }
 
+enum SettingKind {
+    Color,
+    Boolean,
+    Amount,
+    Width,
+    Count,
+    Attributes,
+    DirectoryFormat,
+    Ellipsis,
+    OverflowMode,
+    MouseAction,
+    SessionNamePattern,
+    Template(&'static TemplateSpec),
+    Text,
+}
+
+const INPUT_MODES: [InputMode; 14] = [
+    InputMode::Normal,
+    InputMode::Locked,
+    InputMode::Resize,
+    InputMode::Pane,
+    InputMode::Tab,
+    InputMode::Scroll,
+    InputMode::EnterSearch,
+    InputMode::Search,
+    InputMode::RenameTab,
+    InputMode::RenamePane,
+    InputMode::Session,
+    InputMode::Move,
+    InputMode::Prompt,
+    InputMode::Tmux,
+];
+
 impl UserConfiguration {
-    fn str_to_palette_color(color_str: &str) -> Option<PaletteColor> {
-        let color_parts = color_str
-            .split(",")
-            .filter_map(|part| part.parse::<u8>().ok())
-            .collect::<Vec<_>>();
-        Some(match color_parts.len() {
-            1 => PaletteColor::EightBit(color_parts[0]),
-            3 => PaletteColor::Rgb((color_parts[0], color_parts[1], color_parts[2])),
-            _ => {
-                eprintln!("{color_str} is not a valid color");
-                return None;
+    fn default_color(color_query: &str) -> Option<String> {
+        Some(
+            match color_query {
+                "FgColor" | "SessionDirectoryColor" => "theme.text_selected.base",
+                "BgColor" => "theme.text_unselected.background",
+                "SessionNameColor" | "TabColor" => "theme.text_selected.background",
+                "ActiveTabColor" => "theme.text_selected.emphasis_2",
+                "NormalModeColor" => "theme.exit_code_error.emphasis_1",
+                "OtherModesColor" | "OthersColor" => "theme.text_selected.emphasis_0",
+                "ConfigErrorColor" | "FailedCommandIndicatorColor" | "GitDirtyColor" => {
+                    "theme.exit_code_error.base"
+                }
+                "GitBranchColor" => "theme.text_selected.emphasis_3",
+                "GitAheadBehindColor" => "FgColor",
+                "GitBgColor" => "BgColor",
+                "SessionDirectoryBgColor" | "SessionNameBgColor" | "TabBgColor" => "BgColor",
+                "ActiveTabBgColor" => "TabBgColor",
+                "SeparatorColor" | "ClockColor" => "FgColor",
+                "ClockBgColor" => "BgColor",
+                "SyncIndicatorColor"
+                | "FullscreenIndicatorColor"
+                | "FloatingIndicatorColor"
+                | "OtherClientsIndicatorColor"
+                | "SwapLayoutDirtyIndicatorColor" => "TabColor",
+                _ => {
+                    return INPUT_MODES.iter().find_map(|mode| {
+                        if color_query == format!("{mode:?}ModeColor") {
+                            Some("OtherModesColor".to_string())
+                        } else if color_query == format!("{mode:?}ModeBgColor") {
+                            Some("BgColor".to_string())
+                        } else {
+                            None
+                        }
+                    })
+                }
             }
-        })
+            .to_string(),
+        )
     }
     fn get_color_from_configuration(
         configuration: &BTreeMap<String, String>,
+        colors: &Styling,
         color_query: &str,
-        fallback_color: PaletteColor,
     ) -> PaletteColor {
-        if let Some(color_string) = configuration.get(color_query) {
-            if let Some(color) = Self::str_to_palette_color(color_string) {
-                return color;
-            }
+        ColorResolver {
+            configuration,
+            colors,
+            defaults: &Self::default_color,
         }
-        fallback_color
+        .resolve(color_query)
     }
     fn get_string_from_configuration(
         configuration: &BTreeMap<String, String>,
@@ -85,6 +280,31 @@ impl UserConfiguration {
         }
         .to_string()
     }
+    fn get_template_from_configuration(
+        configuration: &BTreeMap<String, String>,
+        query: &str,
+        fallback: &str,
+        spec: &TemplateSpec,
+    ) -> Template {
+        configuration
+            .get(query)
+            .and_then(|template_string| Template::parse(template_string, spec).ok())
+            .unwrap_or_else(|| Template::parse(fallback, spec).unwrap())
+    }
+    fn get_indicator_from_configuration(
+        configuration: &BTreeMap<String, String>,
+        colors: &Styling,
+        query: &str,
+        fallback: &str,
+    ) -> Indicator {
+        let color_query = format!("{query}Color");
+        Indicator {
+            text: Self::get_string_from_configuration(configuration, query, fallback),
+            color: configuration
+                .contains_key(&color_query)
+                .then(|| Self::get_color_from_configuration(configuration, colors, &color_query)),
+        }
+    }
     fn get_bool_from_configuration(
         configuration: &BTreeMap<String, String>,
         query: &str,
@@ -95,84 +315,315 @@ impl UserConfiguration {
             None => fallback,
         }
     }
-    pub fn populate_from_configuration(
+    fn get_amount_from_configuration(
         configuration: &BTreeMap<String, String>,
-        colors: &Styling,
-    ) -> Self {
-        let mode_display: HashMap<InputMode, String> = [
-            InputMode::Normal,
-            InputMode::Locked,
-            InputMode::Resize,
-            InputMode::Pane,
-            InputMode::Tab,
-            InputMode::Scroll,
-            InputMode::EnterSearch,
-            InputMode::Search,
-            InputMode::RenameTab,
-            InputMode::RenamePane,
-            InputMode::Session,
-            InputMode::Move,
-            InputMode::Prompt,
-            InputMode::Tmux,
-        ]
-        .iter()
-        .cloned()
-        .map(|mode| {
-            let mode_string = format!("{:?}", mode);
-            let fallback = if mode == InputMode::Locked {
-                String::new()
+        query: &str,
+        fallback: f64,
+    ) -> f64 {
+        match configuration.get(query) {
+            Some(value) => parse_amount(value).unwrap_or(fallback),
+            None => fallback,
+        }
+    }
+    fn get_number_from_configuration(
+        configuration: &BTreeMap<String, String>,
+        query: &str,
+        fallback: usize,
+    ) -> usize {
+        match configuration.get(query) {
+            Some(value) => value.trim().parse().unwrap_or(fallback),
+            None => fallback,
+        }
+    }
+    // `{target}{event}` settings, e.g. `TabRightClick`, where the ones of the bar apply to the
+    // targets that don't have a binding of their own
+    fn get_mouse_bindings_from_configuration(
+        configuration: &BTreeMap<String, String>,
+    ) -> HashMap<(MouseTarget, MouseEvent), MouseAction> {
+        let get_action = |key: String| {
+            configuration
+                .get(&key)
+                .and_then(|action| MouseAction::parse(action).ok())
+        };
+        let collapsed_tabs_action =
+            if Self::get_bool_from_configuration(configuration, "CollapsedTabsPicker", true) {
+                MouseAction::Picker
             } else {
-                mode_string.chars().next().unwrap().to_uppercase().collect()
+                MouseAction::Focus
             };
-            (
-                mode,
-                Self::get_string_from_configuration(
-                    configuration,
-                    format!("{mode_string}ModeLabel").as_str(),
-                    &fallback,
-                ),
-            )
+        let mut mouse_bindings = HashMap::new();
+        for (target, target_name) in MOUSE_TARGETS {
+            for (event, event_name) in MOUSE_EVENTS {
+                let target_default = match (target, event) {
+                    (MouseTarget::Tab, MouseEvent::LeftClick) => Some(MouseAction::Focus),
+                    (MouseTarget::CollapsedTabs, MouseEvent::LeftClick) => {
+                        Some(collapsed_tabs_action)
+                    }
+                    _ => None,
+                };
+                let bar_default = match event {
+                    MouseEvent::ScrollUp => MouseAction::Next,
+                    MouseEvent::ScrollDown => MouseAction::Previous,
+                    _ => MouseAction::None,
+                };
+                let action = get_action(format!("{target_name}{event_name}"))
+                    .or(target_default)
+                    .or_else(|| get_action(format!("Bar{event_name}")))
+                    .unwrap_or(bar_default);
+                mouse_bindings.insert((target, event), action);
+            }
+        }
+        mouse_bindings
+    }
+    fn get_attributes_from_configuration(
+        configuration: &BTreeMap<String, String>,
+        query: &str,
+        fallback: &str,
+    ) -> TextAttributes {
+        configuration
+            .get(query)
+            .and_then(|value| TextAttributes::parse(value).ok())
+            .unwrap_or_else(|| TextAttributes::parse(fallback).unwrap())
+    }
+    fn setting_kind(key: &str) -> Option<SettingKind> {
+        if Self::default_color(key).is_some() {
+            return Some(SettingKind::Color);
+        }
+        Some(match key {
+            "DisplaySessionDirectory"
+            | "ModeTheming"
+            | "PowerlineSeparators"
+            | "AutoRenameTabs"
+            | "SessionDirectoryFromActiveTab"
+            | "AbbreviateHome"
+            | "ShortenPaths"
+            | "AdaptiveTabNames"
+            | "CollapsedTabsPicker"
+            | "InvertScroll"
+            | "WrapAround" => SettingKind::Boolean,
+            "ModeTintStrength" => SettingKind::Amount,
+            "PaneTitleMaxWidth"
+            | "SessionDirectoryMaxWidth"
+            | "MaxTabNameWidth"
+            | "ActiveMaxTabNameWidth" => SettingKind::Width,
+            "GitRefreshInterval" | "SessionNameParts" => SettingKind::Count,
+            "TabNameEllipsis" => SettingKind::Ellipsis,
+            "OverflowMode" => SettingKind::OverflowMode,
+            "SessionDirectoryFormat" => SettingKind::DirectoryFormat,
+            "SessionNamePattern" => SettingKind::SessionNamePattern,
+            "SessionDirectoryStyle"
+            | "SessionNameStyle"
+            | "ModeStyle"
+            | "TabStyle"
+            | "ActiveTabStyle"
+            | "OthersStyle"
+            | "ClockStyle"
+            | "GitStyle" => SettingKind::Attributes,
+            "BarFormat" => SettingKind::Template(&BAR_TEMPLATE),
+            "TabFormat" | "ActiveTabFormat" => SettingKind::Template(&TAB_TEMPLATE),
+            "DefaultTabName"
+            | "SegmentSeparator"
+            | "TabSeparator"
+            | "ActiveTabLeftSeparator"
+            | "ActiveTabRightSeparator"
+            | "ClockFormat"
+            | "SyncIndicator"
+            | "FullscreenIndicator"
+            | "FloatingIndicator"
+            | "OtherClientsIndicator"
+            | "SwapLayoutDirtyIndicator"
+            | "FailedCommandIndicator"
+            | "SessionDirectoryCommand"
+            | "SessionDirectoryPlaceholder"
+            | "GitDirtyIndicator"
+            | "SessionNameSeparator"
+            | "PickerTabs" => SettingKind::Text,
+            _ if INPUT_MODES
+                .iter()
+                .any(|mode| key == format!("{mode:?}ModeLabel")) =>
+            {
+                SettingKind::Text
+            }
+            _ if MOUSE_TARGETS.iter().any(|(_, target)| {
+                MOUSE_EVENTS
+                    .iter()
+                    .any(|(_, event)| key == format!("{target}{event}"))
+            }) =>
+            {
+                SettingKind::MouseAction
+            }
+            _ => return None,
         })
-        .collect();
+    }
+    // collects every setting that is unknown or whose value can't be used
+    fn validate_configuration(
+        configuration: &BTreeMap<String, String>,
+        colors: &Styling,
+    ) -> Vec<ConfigError> {
+        let color_resolver = ColorResolver {
+            configuration,
+            colors,
+            defaults: &Self::default_color,
+        };
+        configuration
+            .iter()
+            .filter_map(|(key, value)| {
+                let reason = match Self::setting_kind(key) {
+                    None => "unknown setting".to_string(),
+                    Some(SettingKind::Color) => color_resolver.check(value).err()?,
+                    Some(SettingKind::Boolean) => value
+                        .parse::<bool>()
+                        .err()
+                        .map(|_| "expected true or false".to_string())?,
+                    Some(SettingKind::Amount) => parse_amount(value).err()?,
+                    Some(SettingKind::Width) => value
+                        .trim()
+                        .parse::<usize>()
+                        .err()
+                        .map(|_| "expected a number of columns".to_string())?,
+                    Some(SettingKind::Count) => value
+                        .trim()
+                        .parse::<usize>()
+                        .err()
+                        .map(|_| "expected a non-negative integer".to_string())?,
+                    Some(SettingKind::Attributes) => TextAttributes::parse(value).err()?,
+                    Some(SettingKind::DirectoryFormat) => DirectoryFormat::parse(value).err()?,
+                    Some(SettingKind::Ellipsis) => Ellipsis::parse(value).err()?,
+                    Some(SettingKind::OverflowMode) => OverflowMode::parse(value).err()?,
+                    Some(SettingKind::MouseAction) => MouseAction::parse(value).err()?,
+                    Some(SettingKind::SessionNamePattern) => {
+                        parse_session_name_pattern(value).err()?
+                    }
+                    Some(SettingKind::Template(spec)) => Template::parse(value, spec).err()?,
+                    Some(SettingKind::Text) => return None,
+                };
+                Some(ConfigError {
+                    key: key.clone(),
+                    value: value.clone(),
+                    reason,
+                })
+            })
+            .collect()
+    }
+    pub fn populate_from_configuration(
+        configuration: &BTreeMap<String, String>,
+        colors: &Styling,
+    ) -> Self {
+        let mode_display: HashMap<InputMode, String> = INPUT_MODES
+            .iter()
+            .cloned()
+            .map(|mode| {
+                let mode_string = format!("{:?}", mode);
+                let fallback = if mode == InputMode::Locked {
+                    String::new()
+                } else {
+                    mode_string.chars().next().unwrap().to_uppercase().collect()
+                };
+                (
+                    mode,
+                    Self::get_string_from_configuration(
+                        configuration,
+                        format!("{mode_string}ModeLabel").as_str(),
+                        &fallback,
+                    ),
+                )
+            })
+            .collect();
+        let mode_colors: HashMap<InputMode, (PaletteColor, PaletteColor)> = INPUT_MODES
+            .iter()
+            .map(|mode| {
+                (
+                    *mode,
+                    (
+                        Self::get_color_from_configuration(
+                            configuration,
+                            colors,
+                            &format!("{mode:?}ModeColor"),
+                        ),
+                        Self::get_color_from_configuration(
+                            configuration,
+                            colors,
+                            &format!("{mode:?}ModeBgColor"),
+                        ),
+                    ),
+                )
+            })
+            .collect();
+
+        let tab_format = Self::get_template_from_configuration(
+            configuration,
+            "TabFormat",
+            DEFAULT_TAB_FORMAT,
+            &TAB_TEMPLATE,
+        );
+        let active_tab_format = match configuration.get("ActiveTabFormat") {
+            Some(_) => Self::get_template_from_configuration(
+                configuration,
+                "ActiveTabFormat",
+                DEFAULT_TAB_FORMAT,
+                &TAB_TEMPLATE,
+            ),
+            None => tab_format.clone(),
+        };
 
-        let white = colors.text_selected.base;
-        let black = colors.text_unselected.background;
-        let gray = colors.text_selected.background;
-        let green = colors.text_selected.emphasis_2;
-        let gold = colors.exit_code_error.emphasis_1;
-        let orange = colors.text_selected.emphasis_0;
+        let tab_attributes =
+            Self::get_attributes_from_configuration(configuration, "TabStyle", "bold");
+        let active_tab_attributes = match configuration.get("ActiveTabStyle") {
+            Some(_) => {
+                Self::get_attributes_from_configuration(configuration, "ActiveTabStyle", "bold")
+            }
+            None => tab_attributes,
+        };
 
         Self {
             mode_display,
-            color_fg: Self::get_color_from_configuration(configuration, "FgColor", white),
-            color_bg: Self::get_color_from_configuration(configuration, "BgColor", black),
+            mode_colors,
+            color_fg: Self::get_color_from_configuration(configuration, colors, "FgColor"),
+            color_bg: Self::get_color_from_configuration(configuration, colors, "BgColor"),
             color_session_directory: Self::get_color_from_configuration(
                 configuration,
+                colors,
                 "SessionDirectoryColor",
-                white,
             ),
             color_session_name: Self::get_color_from_configuration(
                 configuration,
+                colors,
                 "SessionNameColor",
-                gray,
             ),
-            color_tab: Self::get_color_from_configuration(configuration, "TabColor", gray),
+            color_tab: Self::get_color_from_configuration(configuration, colors, "TabColor"),
             color_active_tab: Self::get_color_from_configuration(
                 configuration,
+                colors,
                 "ActiveTabColor",
-                green,
             ),
-            color_normal_mode: Self::get_color_from_configuration(
+            color_others: Self::get_color_from_configuration(configuration, colors, "OthersColor"),
+            color_session_directory_bg: Self::get_color_from_configuration(
+                configuration,
+                colors,
+                "SessionDirectoryBgColor",
+            ),
+            color_session_name_bg: Self::get_color_from_configuration(
+                configuration,
+                colors,
+                "SessionNameBgColor",
+            ),
+            color_tab_bg: Self::get_color_from_configuration(configuration, colors, "TabBgColor"),
+            color_active_tab_bg: Self::get_color_from_configuration(
+                configuration,
+                colors,
+                "ActiveTabBgColor",
+            ),
+            color_separator: Self::get_color_from_configuration(
                 configuration,
-                "NormalModeColor",
-                gold,
+                colors,
+                "SeparatorColor",
             ),
-            color_other_modes: Self::get_color_from_configuration(
+            color_clock: Self::get_color_from_configuration(configuration, colors, "ClockColor"),
+            color_clock_bg: Self::get_color_from_configuration(
                 configuration,
-                "OtherModesColor",
-                orange,
+                colors,
+                "ClockBgColor",
             ),
-            color_others: Self::get_color_from_configuration(configuration, "OthersColor", orange),
             default_tab_name: Self::get_string_from_configuration(
                 configuration,
                 "DefaultTabName",
@@ -183,14 +634,601 @@ impl UserConfiguration {
                 "DisplaySessionDirectory",
                 true,
             ),
+            bar_format: Self::get_template_from_configuration(
+                configuration,
+                "BarFormat",
+                DEFAULT_BAR_FORMAT,
+                &BAR_TEMPLATE,
+            ),
+            tab_format,
+            active_tab_format,
+            index_only_tab_format: Template::parse(INDEX_ONLY_TAB_FORMAT, &TAB_TEMPLATE)
+                .unwrap_or_default(),
+            color_config_errors: Self::get_color_from_configuration(
+                configuration,
+                colors,
+                "ConfigErrorColor",
+            ),
+            config_errors: Self::validate_configuration(configuration, colors),
+            mode_theming: Self::get_bool_from_configuration(configuration, "ModeTheming", false),
+            mode_tint_strength: Self::get_amount_from_configuration(
+                configuration,
+                "ModeTintStrength",
+                0.15,
+            ),
+            session_directory_attributes: Self::get_attributes_from_configuration(
+                configuration,
+                "SessionDirectoryStyle",
+                "bold",
+            ),
+            session_name_attributes: Self::get_attributes_from_configuration(
+                configuration,
+                "SessionNameStyle",
+                "bold,italic",
+            ),
+            mode_attributes: Self::get_attributes_from_configuration(
+                configuration,
+                "ModeStyle",
+                "bold",
+            ),
+            tab_attributes,
+            active_tab_attributes,
+            others_attributes: Self::get_attributes_from_configuration(
+                configuration,
+                "OthersStyle",
+                "bold",
+            ),
+            segment_separator: Self::get_string_from_configuration(
+                configuration,
+                "SegmentSeparator",
+                "",
+            ),
+            tab_separator: Self::get_string_from_configuration(configuration, "TabSeparator", ""),
+            active_tab_left_separator: Self::get_string_from_configuration(
+                configuration,
+                "ActiveTabLeftSeparator",
+                "",
+            ),
+            active_tab_right_separator: Self::get_string_from_configuration(
+                configuration,
+                "ActiveTabRightSeparator",
+                "",
+            ),
+            powerline_separators: Self::get_bool_from_configuration(
+                configuration,
+                "PowerlineSeparators",
+                false,
+            ),
+            clock_format: Self::get_string_from_configuration(
+                configuration,
+                "ClockFormat",
+                "%H:%M",
+            ),
+            clock_attributes: Self::get_attributes_from_configuration(
+                configuration,
+                "ClockStyle",
+                "bold",
+            ),
+            sync_indicator: Self::get_indicator_from_configuration(
+                configuration,
+                colors,
+                "SyncIndicator",
+                "(Sync)",
+            ),
+            fullscreen_indicator: Self::get_indicator_from_configuration(
+                configuration,
+                colors,
+                "FullscreenIndicator",
+                "",
+            ),
+            floating_indicator: Self::get_indicator_from_configuration(
+                configuration,
+                colors,
+                "FloatingIndicator",
+                "",
+            ),
+            other_clients_indicator: Self::get_indicator_from_configuration(
+                configuration,
+                colors,
+                "OtherClientsIndicator",
+                "",
+            ),
+            swap_layout_dirty_indicator: Self::get_indicator_from_configuration(
+                configuration,
+                colors,
+                "SwapLayoutDirtyIndicator",
+                "",
+            ),
+            failed_command_indicator: Indicator {
+                text: Self::get_string_from_configuration(
+                    configuration,
+                    "FailedCommandIndicator",
+                    "✗",
+                ),
+                color: Some(Self::get_color_from_configuration(
+                    configuration,
+                    colors,
+                    "FailedCommandIndicatorColor",
+                )),
+            },
+            pane_title_max_width: Self::get_number_from_configuration(
+                configuration,
+                "PaneTitleMaxWidth",
+                20,
+            ),
+            auto_rename_tabs: Self::get_bool_from_configuration(
+                configuration,
+                "AutoRenameTabs",
+                false,
+            ),
+            session_directory_from_active_tab: Self::get_bool_from_configuration(
+                configuration,
+                "SessionDirectoryFromActiveTab",
+                false,
+            ),
+            abbreviate_home: Self::get_bool_from_configuration(
+                configuration,
+                "AbbreviateHome",
+                true,
+            ),
+            shorten_paths: Self::get_bool_from_configuration(configuration, "ShortenPaths", false),
+            session_directory_format: configuration
+                .get("SessionDirectoryFormat")
+                .and_then(|format| DirectoryFormat::parse(format).ok())
+                .unwrap_or_default(),
+            session_directory_max_width: Self::get_number_from_configuration(
+                configuration,
+                "SessionDirectoryMaxWidth",
+                0,
+            ),
+            session_directory_placeholder: Self::get_string_from_configuration(
+                configuration,
+                "SessionDirectoryPlaceholder",
+                "?",
+            ),
+            color_git_branch: Self::get_color_from_configuration(
+                configuration,
+                colors,
+                "GitBranchColor",
+            ),
+            color_git_ahead_behind: Self::get_color_from_configuration(
+                configuration,
+                colors,
+                "GitAheadBehindColor",
+            ),
+            color_git_dirty: Self::get_color_from_configuration(
+                configuration,
+                colors,
+                "GitDirtyColor",
+            ),
+            color_git_bg: Self::get_color_from_configuration(configuration, colors, "GitBgColor"),
+            git_attributes: Self::get_attributes_from_configuration(
+                configuration,
+                "GitStyle",
+                "bold",
+            ),
+            git_dirty_indicator: Self::get_string_from_configuration(
+                configuration,
+                "GitDirtyIndicator",
+                "*",
+            ),
+            git_refresh_interval: Self::get_number_from_configuration(
+                configuration,
+                "GitRefreshInterval",
+                5,
+            )
+            .max(1),
+            session_name_separator: Self::get_string_from_configuration(
+                configuration,
+                "SessionNameSeparator",
+                "-",
+            ),
+            session_name_parts: Self::get_number_from_configuration(
+                configuration,
+                "SessionNameParts",
+                2,
+            ),
+            session_name_pattern: configuration
+                .get("SessionNamePattern")
+                .and_then(|pattern| parse_session_name_pattern(pattern).ok()),
+            max_tab_name_width: Self::get_number_from_configuration(
+                configuration,
+                "MaxTabNameWidth",
+                0,
+            ),
+            active_max_tab_name_width: Self::get_number_from_configuration(
+                configuration,
+                "ActiveMaxTabNameWidth",
+                Self::get_number_from_configuration(configuration, "MaxTabNameWidth", 0),
+            ),
+            tab_name_ellipsis: configuration
+                .get("TabNameEllipsis")
+                .and_then(|ellipsis| Ellipsis::parse(ellipsis).ok())
+                .unwrap_or_default(),
+            adaptive_tab_names: Self::get_bool_from_configuration(
+                configuration,
+                "AdaptiveTabNames",
+                false,
+            ),
+            overflow_mode: configuration
+                .get("OverflowMode")
+                .and_then(|mode| OverflowMode::parse(mode).ok())
+                .unwrap_or_default(),
+            mouse_bindings: Self::get_mouse_bindings_from_configuration(configuration),
+            invert_scroll: Self::get_bool_from_configuration(configuration, "InvertScroll", false),
+            wrap_around: Self::get_bool_from_configuration(configuration, "WrapAround", false),
         }
     }
+    // tints the bar with the color of the mode when mode theming is on and dims the tabs
+    // while locked
+    pub fn for_mode(&self, mode: InputMode) -> Self {
+        let mut user_conf = self.clone();
+        if !self.mode_theming || mode == InputMode::Normal {
+            return user_conf;
+        }
+        let (accent, _) = self.mode_colors[&mode];
+        user_conf.color_bg = mix(accent, self.color_bg, self.mode_tint_strength);
+        user_conf.color_active_tab = accent;
+        // backgrounds that weren't set apart from the bar follow its tint
+        let tinted_bg = user_conf.color_bg;
+        let tint = |color: &mut PaletteColor| {
+            if *color == self.color_bg {
+                *color = tinted_bg;
+            }
+        };
+        tint(&mut user_conf.color_session_directory_bg);
+        tint(&mut user_conf.color_session_name_bg);
+        tint(&mut user_conf.color_tab_bg);
+        tint(&mut user_conf.color_active_tab_bg);
+        tint(&mut user_conf.color_git_bg);
+        for (_, mode_bg_color) in user_conf.mode_colors.values_mut() {
+            tint(mode_bg_color);
+        }
+        if mode == InputMode::Locked {
+            user_conf.tab_attributes.dim = true;
+            user_conf.active_tab_attributes.dim = true;
+        }
+        user_conf
+    }
 }
 
-fn pwd() {
+// runs `pwd`, or the configured shell command when there's one
+fn pwd(command: Option<&String>) {
     let mut context = BTreeMap::new();
     context.insert("type".to_string(), "pwd".to_string());
-    run_command(&["pwd"], context);
+    match command {
+        Some(command) => run_command(&["sh", "-c", command], context),
+        None => run_command(&["pwd"], context),
+    }
+}
+
+fn git_root() {
+    let mut context = BTreeMap::new();
+    context.insert("type".to_string(), "git_root".to_string());
+    run_command(&["git", "rev-parse", "--show-toplevel"], context);
+}
+
+fn home() {
+    let mut context = BTreeMap::new();
+    context.insert("type".to_string(), "home".to_string());
+    run_command(&["printenv", "HOME"], context);
+}
+
+fn refresh_clock(clock_format: &str) {
+    let mut context = BTreeMap::new();
+    context.insert("type".to_string(), "clock".to_string());
+    run_command(&["date", &format!("+{clock_format}")], context);
+}
+
+// the clock ticks every minute, or every second if it displays seconds
+fn seconds_until_clock_tick(clock_format: &str) -> f64 {
+    let period = if ["%S", "%s", "%T", "%r", "%X", "%c"]
+        .iter()
+        .any(|seconds_format| clock_format.contains(seconds_format))
+    {
+        1.0
+    } else {
+        60.0
+    };
+    period - now() % period
+}
+
+fn now() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64()
+}
+
+impl State {
+    // refreshes the clock and the git status when they're due, then sets a timeout for the next
+    // refresh
+    fn schedule_refresh(&mut self) {
+        if self.refresh_scheduled || !self.can_run_commands {
+            return;
+        }
+        let mut timeout: Option<f64> = None;
+        let clock_format = &self.user_configuration.clock_format;
+        if self.user_configuration.bar_format.contains_field("clock") {
+            refresh_clock(clock_format);
+            timeout = Some(seconds_until_clock_tick(clock_format));
+        }
+        if self.user_configuration.bar_format.contains_field("git") {
+            if now() >= self.next_git_refresh {
+                self.refresh_git();
+            }
+            let until_git_refresh = self.next_git_refresh - now();
+            timeout = Some(timeout.map_or(until_git_refresh, |t| t.min(until_git_refresh)));
+        }
+        if let Some(timeout) = timeout {
+            set_timeout(timeout);
+            self.refresh_scheduled = true;
+        }
+    }
+
+    fn refresh_git(&mut self) {
+        if !self.can_run_commands || !self.user_configuration.bar_format.contains_field("git") {
+            return;
+        }
+        self.git_directory = self.active_pane_cwd().cloned();
+        self.next_git_refresh = now() + self.user_configuration.git_refresh_interval as f64;
+        let mut context = BTreeMap::new();
+        context.insert("type".to_string(), "git".to_string());
+        let command = ["git", "status", "--porcelain=v2", "--branch"];
+        match self.git_directory.clone() {
+            Some(cwd) => {
+                run_command_with_env_variables_and_cwd(&command, BTreeMap::new(), cwd, context)
+            }
+            None => run_command(&command, context),
+        }
+    }
+
+    // refreshes the git status right away when the active tab moved to another directory
+    fn refresh_git_if_moved(&mut self) {
+        if self.active_pane_cwd() != self.git_directory.as_ref() {
+            self.refresh_git();
+        }
+    }
+
+    // forgets the panes that stopped failing and marks the ones in the active tab as seen
+    fn see_failed_panes(&mut self) {
+        let failed_panes: HashSet<u32> = self
+            .panes
+            .panes
+            .values()
+            .flat_map(|panes| failed_command_panes(panes))
+            .collect();
+        self.seen_failed_panes
+            .retain(|id| failed_panes.contains(id));
+        if let Some(panes) = self
+            .tabs
+            .iter()
+            .find(|tab| tab.active)
+            .and_then(|tab| self.panes.panes.get(&tab.position))
+        {
+            self.seen_failed_panes.extend(failed_command_panes(panes));
+        }
+    }
+
+    // the working directories of panes that were closed are never needed again
+    fn forget_closed_pane_cwds(&mut self) {
+        let pane_ids: HashSet<PaneId> = self
+            .panes
+            .panes
+            .values()
+            .flatten()
+            .map(|pane| {
+                if pane.is_plugin {
+                    PaneId::Plugin(pane.id)
+                } else {
+                    PaneId::Terminal(pane.id)
+                }
+            })
+            .collect();
+        self.pane_cwds.retain(|id, _| pane_ids.contains(id));
+    }
+
+    fn pane_cwd(&self, tab: &TabInfo) -> Option<&PathBuf> {
+        let pane = focused_pane(tab, self.panes.panes.get(&tab.position)?)?;
+        self.pane_cwds.get(&PaneId::Terminal(pane.id))
+    }
+
+    fn active_pane_cwd(&self) -> Option<&PathBuf> {
+        self.pane_cwd(self.tabs.iter().find(|tab| tab.active)?)
+    }
+
+    fn handle_mouse_event(&self, mouse_event: MouseEvent, col: Option<usize>) {
+        let mouse_event = if self.user_configuration.invert_scroll {
+            mouse_event.inverted()
+        } else {
+            mouse_event
+        };
+        let part = col.and_then(|col| get_clicked_line_part(&self.tab_line, col));
+        let target = match part.map(|part| part.kind) {
+            Some(LinePartKind::Tab) => MouseTarget::Tab,
+            Some(LinePartKind::CollapsedTabs) => MouseTarget::CollapsedTabs,
+            Some(LinePartKind::Session) => MouseTarget::Session,
+            Some(LinePartKind::Mode) => MouseTarget::Mode,
+            _ => MouseTarget::Bar,
+        };
+        let action = self
+            .user_configuration
+            .mouse_bindings
+            .get(&(target, mouse_event))
+            .copied()
+            .unwrap_or_default();
+        // tabs are indexed starting from 1
+        let tab_idx = part
+            .and_then(|part| part.tab_index)
+            .map_or(self.active_tab_idx, |position| position + 1);
+        let tab_count = self.tabs.len();
+        if tab_count == 0 {
+            return;
+        }
+        match action {
+            MouseAction::None => {}
+            MouseAction::Focus => {
+                if tab_idx != self.active_tab_idx {
+                    switch_tab_to(tab_idx as u32);
+                }
+            }
+            MouseAction::Picker => {
+                if target == MouseTarget::CollapsedTabs {
+                    self.open_tab_picker(tab_idx - 1);
+                }
+            }
+            MouseAction::Close => {
+                if let Some(tab) = self.tabs.iter().find(|tab| tab.position + 1 == tab_idx) {
+                    close_tab_with_id(tab.tab_id as u64);
+                }
+            }
+            MouseAction::Rename => {
+                switch_tab_to(tab_idx as u32);
+                switch_to_input_mode(&InputMode::RenameTab);
+            }
+            MouseAction::ToggleFullscreen => {
+                switch_tab_to(tab_idx as u32);
+                toggle_focus_fullscreen();
+            }
+            MouseAction::Next => {
+                if self.user_configuration.wrap_around {
+                    switch_tab_to((self.active_tab_idx % tab_count + 1) as u32);
+                } else {
+                    switch_tab_to(min(self.active_tab_idx + 1, tab_count) as u32);
+                }
+            }
+            MouseAction::Previous => {
+                if self.user_configuration.wrap_around {
+                    switch_tab_to(((self.active_tab_idx + tab_count - 2) % tab_count + 1) as u32);
+                } else {
+                    switch_tab_to(max(self.active_tab_idx.saturating_sub(1), 1) as u32);
+                }
+            }
+        }
+    }
+
+    // opens another instance of the plugin as a picker of the tabs hidden behind the collapse
+    // marker that stands for the tab at `tab_index`
+    fn open_tab_picker(&self, tab_index: usize) {
+        let plugin_id = get_plugin_ids().plugin_id;
+        let Some(plugin_url) = self
+            .panes
+            .panes
+            .values()
+            .flatten()
+            .find(|pane| pane.is_plugin && pane.id == plugin_id)
+            .and_then(|pane| pane.plugin_url.clone())
+        else {
+            return;
+        };
+        let active_position = self.active_tab_idx.saturating_sub(1);
+        let hidden_positions = if tab_index < active_position {
+            0..tab_index + 1
+        } else {
+            tab_index..self.tabs.len()
+        };
+        let mut configuration = self.configuration.clone();
+        configuration.insert(
+            "PickerTabs".to_string(),
+            hidden_positions
+                .clone()
+                .map(|position| position.to_string())
+                .collect::<Vec<_>>()
+                .join(","),
+        );
+        // room for the filter and the borders
+        let height = hidden_positions.len().min(20) + 3;
+        open_plugin_pane_floating(
+            &plugin_url,
+            configuration,
+            FloatingPaneCoordinates::new(
+                None,
+                None,
+                Some("50%".to_string()),
+                Some(height.to_string()),
+                None,
+                None,
+            ),
+            BTreeMap::new(),
+        );
+    }
+
+    // working directory of the focused pane of the tab, as it's displayed
+    fn tab_cwd(&self, tab: &TabInfo) -> Option<String> {
+        let mut cwd = self.pane_cwd(tab)?.to_string_lossy().into_owned();
+        if self.user_configuration.abbreviate_home {
+            cwd = abbreviate_home(&cwd, &self.home);
+        }
+        if self.user_configuration.shorten_paths {
+            cwd = shorten(&cwd);
+        }
+        Some(cwd)
+    }
+
+    // renames the tabs that still have their default name, or the one they were given here,
+    // after the command or the working directory of their focused pane
+    fn auto_rename_tabs(&mut self) {
+        if !self.user_configuration.auto_rename_tabs || self.mode_info.mode == InputMode::RenameTab
+        {
+            return;
+        }
+        for tab in &self.tabs {
+            let auto_names = self.auto_tab_names.get(&tab.tab_id);
+            let is_auto_named = is_default_tab_name(&tab.name)
+                || auto_names.is_some_and(|(name, previous_name)| {
+                    tab.name == *name || tab.name == *previous_name
+                });
+            if !is_auto_named {
+                // renamed by the user
+                self.auto_tab_names.remove(&tab.tab_id);
+                continue;
+            }
+            let Some(pane) = self
+                .panes
+                .panes
+                .get(&tab.position)
+                .and_then(|panes| focused_pane(tab, panes))
+                .filter(|pane| !pane.is_plugin)
+            else {
+                continue;
+            };
+            let name = pane
+                .terminal_command
+                .as_deref()
+                .and_then(|command| command.split_whitespace().next())
+                .map(|program| program.rsplit('/').next().unwrap_or(program).to_string())
+                .or_else(|| {
+                    let cwd = self.pane_cwds.get(&PaneId::Terminal(pane.id))?;
+                    let basename = cwd.file_name().unwrap_or(cwd.as_os_str());
+                    Some(basename.to_string_lossy().into_owned())
+                });
+            let Some(name) = name.filter(|name| !name.is_empty() && *name != tab.name) else {
+                continue;
+            };
+            if auto_names.is_some_and(|(last_name, _)| *last_name == name) {
+                // waiting for the rename to be applied
+                continue;
+            }
+            rename_tab_with_id(tab.tab_id as u64, &name);
+            let previous_name = auto_names.map(|(last_name, _)| last_name.clone());
+            self.auto_tab_names
+                .insert(tab.tab_id, (name, previous_name.unwrap_or_default()));
+        }
+    }
+}
+
+fn show_config_errors(config_errors: &[ConfigError]) {
+    let mut args = vec!["%s\n".to_string()];
+    args.extend(
+        config_errors
+            .iter()
+            .map(|error| format!("{}: {:?} ({})", error.key, error.value, error.reason)),
+    );
+    open_command_pane_floating(
+        CommandToRun::new_with_args("printf", args),
+        None,
+        BTreeMap::new(),
+    );
 }
 
 impl ZellijPlugin for State {
@@ -199,66 +1237,137 @@ impl ZellijPlugin for State {
             PermissionType::ReadApplicationState,
             PermissionType::ChangeApplicationState,
             PermissionType::RunCommands,
+            PermissionType::OpenTerminalsOrPlugins,
         ]);
         subscribe(&[
             EventType::TabUpdate,
+            EventType::PaneUpdate,
             EventType::ModeUpdate,
             EventType::Mouse,
             EventType::PermissionRequestResult,
             EventType::RunCommandResult,
+            EventType::Timer,
+            EventType::CwdChanged,
         ]);
+        if let Some(tab_positions) = _configuration.get("PickerTabs") {
+            self.picker = Some(Picker::parse(tab_positions));
+            subscribe(&[EventType::Key]);
+        }
         self.configuration = _configuration;
     }
 
     fn update(&mut self, event: Event) -> bool {
+        if let Some(picker) = self.picker.as_mut() {
+            return match event {
+                Event::Key(key) => picker.handle_key(key, &self.tabs),
+                Event::TabUpdate(tabs) => {
+                    self.tabs = tabs;
+                    true
+                }
+                Event::ModeUpdate(mode_info) => {
+                    self.user_configuration = UserConfiguration::populate_from_configuration(
+                        &self.configuration,
+                        &mode_info.style.colors,
+                    );
+                    self.mode_info = mode_info;
+                    true
+                }
+                _ => false,
+            };
+        }
         let mut should_render = false;
         match event {
             Event::RunCommandResult(_exit_code, _stdout, _stderr, _context) => {
                 if let Some(value) = _context.get("type") {
                     let value: &str = value.as_ref();
                     if value == "pwd" {
-                        self.session_directory = std::str::from_utf8(_stdout.as_slice())
-                            .unwrap()
-                            .trim()
-                            .split('/')
-                            .next_back()
-                            .unwrap()
-                            .to_string();
+                        self.session_directory =
+                            String::from_utf8_lossy(&_stdout).trim().to_string();
+                        self.session_directory_failed =
+                            _exit_code != Some(0) || self.session_directory.is_empty();
+                    } else if value == "git_root" {
+                        self.git_root = (_exit_code == Some(0))
+                            .then(|| String::from_utf8_lossy(&_stdout).trim().to_string());
+                    } else if value == "home" {
+                        self.home = String::from_utf8_lossy(&_stdout).trim().to_string();
+                    } else if value == "git" {
+                        self.git = (_exit_code == Some(0))
+                            .then(|| GitStatus::parse(&String::from_utf8_lossy(&_stdout)));
+                    } else if value == "clock" {
+                        self.clock = String::from_utf8_lossy(&_stdout).trim().to_string();
                     }
                 }
                 should_render = true;
             }
+            Event::Timer(_) => {
+                self.refresh_scheduled = false;
+                self.schedule_refresh();
+            }
             Event::ModeUpdate(mode_info) => {
                 self.user_configuration = UserConfiguration::populate_from_configuration(
                     &self.configuration,
                     &mode_info.style.colors,
                 );
                 self.mode_info = mode_info;
+                self.schedule_refresh();
                 should_render = true;
             }
             Event::TabUpdate(tabs) => {
                 self.active_tab_idx = tabs.iter().position(|t| t.active).unwrap() + 1;
                 self.tabs = tabs;
+                self.see_failed_panes();
+                self.auto_rename_tabs();
+                self.refresh_git_if_moved();
+                should_render = true;
+            }
+            Event::PaneUpdate(panes) => {
+                self.panes = panes;
+                self.forget_closed_pane_cwds();
+                self.see_failed_panes();
+                self.auto_rename_tabs();
+                self.refresh_git_if_moved();
                 should_render = true;
             }
-            Event::Mouse(me) => match me {
-                Mouse::LeftClick(_, col) => {
-                    let tab_to_focus = get_tab_to_focus(&self.tab_line, self.active_tab_idx, col);
-                    if let Some(idx) = tab_to_focus {
-                        switch_tab_to(idx.try_into().unwrap());
+            Event::CwdChanged(pane_id, cwd, _focused_clients) => {
+                self.pane_cwds.insert(pane_id, cwd);
+                self.auto_rename_tabs();
+                self.refresh_git_if_moved();
+                should_render = true;
+            }
+            Event::Mouse(me) => {
+                let (mouse_event, col) = match me {
+                    Mouse::LeftClick(_, col) => (MouseEvent::LeftClick, Some(col)),
+                    Mouse::RightClick(_, col) => (MouseEvent::RightClick, Some(col)),
+                    // scrolling targets whatever the mouse was last over
+                    Mouse::ScrollUp(_) => (MouseEvent::ScrollUp, self.hover_col),
+                    Mouse::ScrollDown(_) => (MouseEvent::ScrollDown, self.hover_col),
+                    Mouse::Hover(_, col) => {
+                        self.hover_col = Some(col);
+                        return should_render;
+                    }
+                    _ => return should_render,
+                };
+                if let Some(col) = col.filter(|_| mouse_event == MouseEvent::LeftClick) {
+                    if let Some(pane_id) = get_clicked_failed_pane(&self.tab_line, col) {
+                        focus_terminal_pane(pane_id, false, false);
+                        return should_render;
+                    }
+                    if get_clicked_line_part(&self.tab_line, col)
+                        .is_some_and(|part| part.kind == LinePartKind::ConfigErrors)
+                    {
+                        show_config_errors(&self.user_configuration.config_errors);
+                        return should_render;
                     }
                 }
-                Mouse::ScrollUp(_) => {
-                    switch_tab_to(min(self.active_tab_idx + 1, self.tabs.len()) as u32);
-                }
-                Mouse::ScrollDown(_) => {
-                    switch_tab_to(max(self.active_tab_idx.saturating_sub(1), 1) as u32);
-                }
-                _ => {}
-            },
+                self.handle_mouse_event(mouse_event, col);
+            }
             Event::PermissionRequestResult(_) => {
                 set_selectable(false);
-                pwd();
+                self.can_run_commands = true;
+                pwd(self.configuration.get("SessionDirectoryCommand"));
+                git_root();
+                home();
+                self.schedule_refresh();
                 switch_to_input_mode(&InputMode::Locked);
             }
             _ => {
@@ -269,43 +1378,84 @@ impl ZellijPlugin for State {
     }
 
     fn render(&mut self, _rows: usize, cols: usize) {
+        if let Some(picker) = &self.picker {
+            picker.render(&self.tabs, _rows, cols, &self.user_configuration);
+            return;
+        }
         if self.tabs.is_empty() {
             return;
         }
-        let mut all_tabs: Vec<LinePart> = vec![];
-        let mut active_tab_index = 0;
-        let mut is_alternate_tab = false; // NOTE: In case I need it in the future
-        for t in &mut self.tabs {
-            let mut tabname = t.name.clone();
-            if t.active && self.mode_info.mode == InputMode::RenameTab {
-                if tabname.is_empty() {
+        let user_conf = self.user_configuration.for_mode(self.mode_info.mode);
+        let active_tab_index = self.tabs.iter().position(|t| t.active).unwrap_or_default();
+        let render_tabs = |label: TabLabel| {
+            let mut all_tabs: Vec<LinePart> = vec![];
+            let mut is_alternate_tab = false; // NOTE: In case I need it in the future
+            for t in &self.tabs {
+                let mut tabname = t.name.clone();
+                if t.active && self.mode_info.mode == InputMode::RenameTab && tabname.is_empty() {
                     tabname = String::from("Enter name...");
                 }
-                active_tab_index = t.position;
-            } else if t.active {
-                active_tab_index = t.position;
+                let panes = self
+                    .panes
+                    .panes
+                    .get(&t.position)
+                    .map(Vec::as_slice)
+                    .unwrap_or_default();
+                let context = TabContext {
+                    panes,
+                    failed_pane: failed_command_panes(panes)
+                        .find(|id| !self.seen_failed_panes.contains(id)),
+                    cwd: self.tab_cwd(t),
+                    label,
+                };
+                let tab = tab_style(tabname, t, context, &user_conf);
+                is_alternate_tab = !is_alternate_tab;
+                all_tabs.push(tab);
             }
-            let tab = tab_style(tabname, t, self.user_configuration.clone());
-            is_alternate_tab = !is_alternate_tab;
-            all_tabs.push(tab);
-        }
+            all_tabs
+        };
         let Some(session_name) = self.mode_info.session_name.clone() else {
             return;
         };
-        self.tab_line = tab_line(
+        let session_directory = match self.tab_cwd(&self.tabs[active_tab_index]) {
+            Some(cwd) if user_conf.session_directory_from_active_tab => cwd,
+            _ if self.session_directory_failed => user_conf.session_directory_placeholder.clone(),
+            _ => user_conf.session_directory_format.apply(
+                &self.session_directory,
+                &self.home,
+                self.git_root.as_deref(),
+            ),
+        };
+        let session_directory = match user_conf.session_directory_max_width {
+            0 => session_directory,
+            max_width => truncate_middle(&session_directory, max_width),
+        };
+        let context = BarContext {
             session_name,
-            all_tabs,
-            active_tab_index,
+            session_directory,
+            mode: self.mode_info.mode,
+            active_tab: &self.tabs[active_tab_index],
+            clock: self.clock.clone(),
+            git: self.git.clone(),
+            first_visible_tab: self.first_visible_tab,
+            longest_tab_name: self
+                .tabs
+                .iter()
+                .map(|tab| tab_name_width(&tab.name, &user_conf))
+                .max()
+                .unwrap_or_default(),
+        };
+        (self.tab_line, self.first_visible_tab) = tab_line(
+            context,
+            &render_tabs,
             cols.saturating_sub(1),
-            self.user_configuration.clone(),
-            self.mode_info.mode,
-            self.session_directory.clone(),
+            user_conf.clone(),
         );
         let output = self
             .tab_line
             .iter()
             .fold(String::new(), |output, part| output + &part.part);
-        let background = self.user_configuration.color_bg;
+        let background = user_conf.color_bg;
         match background {
             PaletteColor::Rgb((r, g, b)) => {
                 print!("{}\u{1b}[48;2;{};{};{}m\u{1b}[0K", output, r, g, b);
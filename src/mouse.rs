@@ -0,0 +1,81 @@
+/// What a mouse event happened over
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseTarget {
+    Tab,
+    // the session name or directory
+    Session,
+    Mode,
+    CollapsedTabs,
+    // anywhere else
+    Bar,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseEvent {
+    LeftClick,
+    RightClick,
+    ScrollUp,
+    ScrollDown,
+}
+
+/// What a mouse event does, to the tab it happened over or else the active tab
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum MouseAction {
+    #[default]
+    None,
+    Focus,
+    // lists the tabs hidden behind a collapse marker
+    Picker,
+    Close,
+    Rename,
+    ToggleFullscreen,
+    Next,
+    Previous,
+}
+
+pub const MOUSE_TARGETS: [(MouseTarget, &str); 5] = [
+    (MouseTarget::Tab, "Tab"),
+    (MouseTarget::Session, "Session"),
+    (MouseTarget::Mode, "Mode"),
+    (MouseTarget::CollapsedTabs, "CollapsedTabs"),
+    (MouseTarget::Bar, "Bar"),
+];
+
+pub const MOUSE_EVENTS: [(MouseEvent, &str); 4] = [
+    (MouseEvent::LeftClick, "LeftClick"),
+    (MouseEvent::RightClick, "RightClick"),
+    (MouseEvent::ScrollUp, "ScrollUp"),
+    (MouseEvent::ScrollDown, "ScrollDown"),
+];
+
+impl MouseEvent {
+    pub fn inverted(self) -> Self {
+        match self {
+            Self::ScrollUp => Self::ScrollDown,
+            Self::ScrollDown => Self::ScrollUp,
+            event => event,
+        }
+    }
+}
+
+impl MouseAction {
+    pub fn parse(action: &str) -> Result<Self, String> {
+        Ok(match action.trim() {
+            "none" => Self::None,
+            "focus" => Self::Focus,
+            "picker" => Self::Picker,
+            "close" => Self::Close,
+            "rename" => Self::Rename,
+            "toggle-fullscreen" => Self::ToggleFullscreen,
+            "next" => Self::Next,
+            "previous" => Self::Previous,
+            _ => {
+                return Err(
+                    "expected none, focus, picker, close, rename, toggle-fullscreen, \
+                            next or previous"
+                        .to_string(),
+                )
+            }
+        })
+    }
+}
@@ -0,0 +1,85 @@
+/// Replaces the home directory at the start of the path with `~`
+pub fn abbreviate_home(path: &str, home: &str) -> String {
+    let home = home.trim_end_matches('/');
+    if home.is_empty() {
+        return path.to_string();
+    }
+    match path.strip_prefix(home) {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => format!("~{rest}"),
+        _ => path.to_string(),
+    }
+}
+
+/// Shortens every component but the last one to its first character like fish does, e.g.
+/// `~/projects/zellij-cb` becomes `~/p/zellij-cb`. Hidden directories keep their dot
+pub fn shorten(path: &str) -> String {
+    let components: Vec<&str> = path.split('/').collect();
+    let last = components.len() - 1;
+    components
+        .iter()
+        .enumerate()
+        .map(|(i, component)| {
+            if i == last || *component == "~" {
+                return component.to_string();
+            }
+            let prefix_len = if component.starts_with('.') { 2 } else { 1 };
+            component.chars().take(prefix_len).collect()
+        })
+        .collect::<Vec<String>>()
+        .join("/")
+}
+
+/// How the session directory is displayed
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum DirectoryFormat {
+    #[default]
+    Basename,
+    Full,
+    // `~/src/app`
+    HomeRelative,
+    // `~/s/app`
+    Shortened,
+    LastComponents(usize),
+    // name of the root of the enclosing git repository
+    GitRoot,
+}
+
+impl DirectoryFormat {
+    pub fn parse(format: &str) -> Result<Self, String> {
+        let format = format.trim();
+        Ok(match format {
+            "basename" => Self::Basename,
+            "full" => Self::Full,
+            "home-relative" => Self::HomeRelative,
+            "shortened" => Self::Shortened,
+            "git-root" => Self::GitRoot,
+            _ => format
+                .strip_prefix("last-")
+                .and_then(|format| format.strip_suffix("-components"))
+                .and_then(|count| count.parse().ok())
+                .map(Self::LastComponents)
+                .ok_or_else(|| {
+                    "expected basename, full, home-relative, shortened, last-N-components or \
+                     git-root"
+                        .to_string()
+                })?,
+        })
+    }
+
+    // `git_root` is the root of the repository containing the directory, if any
+    pub fn apply(self, directory: &str, home: &str, git_root: Option<&str>) -> String {
+        let basename = |path: &str| path.rsplit('/').next().unwrap_or(path).to_string();
+        match self {
+            Self::Basename => basename(directory),
+            Self::Full => directory.to_string(),
+            Self::HomeRelative => abbreviate_home(directory, home),
+            Self::Shortened => shorten(&abbreviate_home(directory, home)),
+            Self::LastComponents(count) => {
+                let path = abbreviate_home(directory, home);
+                let components: Vec<&str> = path.split('/').collect();
+                components[components.len().saturating_sub(count)..].join("/")
+            }
+            Self::GitRoot => basename(git_root.unwrap_or(directory)),
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use zellij_tile::prelude::*;
+use zellij_tile_utils::style;
+
+use crate::tab::tab_indicators;
+use crate::text::truncate_end;
+use crate::UserConfiguration;
+
+/// Floating list of the tabs hidden behind a collapse marker, which switches to the chosen one
+#[derive(Debug, Default)]
+pub struct Picker {
+    // positions of the tabs it lists
+    tab_positions: Vec<usize>,
+    filter: String,
+    // index of the selected tab among the ones matching the filter
+    selected: usize,
+}
+
+// whether the characters of the filter appear in order in the text, ignoring case
+fn fuzzy_match(text: &str, filter: &str) -> bool {
+    let mut text = text.chars().flat_map(char::to_lowercase);
+    filter
+        .chars()
+        .flat_map(char::to_lowercase)
+        .all(|c| text.any(|t| t == c))
+}
+
+impl Picker {
+    // `tab_positions` is a comma separated list, as it's passed in the plugin configuration
+    pub fn parse(tab_positions: &str) -> Self {
+        Self {
+            tab_positions: tab_positions
+                .split(',')
+                .filter_map(|position| position.trim().parse().ok())
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn matching_tabs<'a>(&self, tabs: &'a [TabInfo]) -> Vec<&'a TabInfo> {
+        self.tab_positions
+            .iter()
+            .filter_map(|position| tabs.iter().find(|tab| tab.position == *position))
+            .filter(|tab| fuzzy_match(&format!("{} {}", tab.position + 1, tab.name), &self.filter))
+            .collect()
+    }
+
+    // returns whether the picker has to be rendered again
+    pub fn handle_key(&mut self, key: KeyWithModifier, tabs: &[TabInfo]) -> bool {
+        let matching_count = self.matching_tabs(tabs).len();
+        match key.bare_key {
+            BareKey::Up => self.selected = self.selected.saturating_sub(1),
+            BareKey::Down => {
+                self.selected = (self.selected + 1).min(matching_count.saturating_sub(1))
+            }
+            BareKey::Enter => {
+                if let Some(tab) = self.matching_tabs(tabs).get(self.selected) {
+                    switch_tab_to(tab.position as u32 + 1);
+                }
+                close_self();
+                return false;
+            }
+            BareKey::Esc => {
+                close_self();
+                return false;
+            }
+            BareKey::Backspace => {
+                self.filter.pop();
+                self.selected = 0;
+            }
+            BareKey::Char(c)
+                if !key.key_modifiers.contains(&KeyModifier::Ctrl)
+                    && !key.key_modifiers.contains(&KeyModifier::Alt) =>
+            {
+                self.filter.push(c);
+                self.selected = 0;
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    pub fn render(
+        &self,
+        tabs: &[TabInfo],
+        rows: usize,
+        cols: usize,
+        user_conf: &UserConfiguration,
+    ) {
+        let filter_style = style!(user_conf.color_fg, user_conf.color_bg).bold();
+        println!(
+            "{}",
+            filter_style.paint(truncate_end(&format!("> {}", self.filter), cols))
+        );
+        let matching_tabs = self.matching_tabs(tabs);
+        // keeps the selected tab in view
+        let visible_rows = rows.saturating_sub(1);
+        let first_row = (self.selected + 1).saturating_sub(visible_rows);
+        for (i, tab) in matching_tabs
+            .iter()
+            .enumerate()
+            .skip(first_row)
+            .take(visible_rows)
+        {
+            let indicators = tab_indicators(tab, false, user_conf)
+                .into_iter()
+                .map(|(_, indicator)| indicator.text)
+                .collect::<Vec<_>>()
+                .join(" ");
+            let row = truncate_end(
+                &format!("{} {} {indicators}", tab.position + 1, tab.name),
+                cols,
+            );
+            let row_style = if i == self.selected {
+                user_conf.active_tab_attributes.apply(style!(
+                    user_conf.color_active_tab,
+                    user_conf.color_active_tab_bg
+                ))
+            } else {
+                user_conf
+                    .tab_attributes
+                    .apply(style!(user_conf.color_tab, user_conf.color_tab_bg))
+            };
+            println!("{}", row_style.paint(row));
+        }
+    }
+}
@@ -1,14 +1,194 @@
+use crate::format::{Piece, Template, TemplateSpec};
+use crate::text::{display_width, truncate_end};
 use crate::LinePart;
+use crate::LinePartKind;
 use crate::UserConfiguration;
 use zellij_tile::prelude::*;
 use zellij_tile_utils::style;
 
-pub fn render_tab(text: String, tab: &TabInfo, user_conf: UserConfiguration) -> LinePart {
-    let background_color = user_conf.color_bg;
-    let foreground_color = if tab.active {
-        user_conf.color_active_tab
+pub const TAB_TEMPLATE: TemplateSpec = TemplateSpec {
+    fields: &[
+        "index",
+        "name",
+        "panes",
+        "terminals",
+        "floating_terminals",
+        "pane_title",
+        "cwd",
+        "indicators",
+        "failed",
+        "sync",
+        "fullscreen",
+        "floating",
+        "clients",
+        "dirty",
+    ],
+    conditions: &[
+        "first",
+        "terminals",
+        "floating_terminals",
+        "pane_title",
+        "cwd",
+        "indicators",
+        "failed",
+        "sync",
+        "fullscreen",
+        "floating",
+        "clients",
+        "dirty",
+    ],
+    blocks: &[],
+    unique_fields: &[],
+};
+
+pub const DEFAULT_TAB_FORMAT: &str = "{?!first: }{index} {name}{?indicators: {indicators}} ";
+
+// label of the inactive tabs when the overflow mode compresses them
+pub const INDEX_ONLY_TAB_FORMAT: &str = "{?!first: }{index} ";
+
+/// Glyph displayed while a tab is in a given state
+#[derive(Default, Clone, Debug)]
+pub struct Indicator {
+    pub text: String,
+    // the color of the tab is used when this isn't set
+    pub color: Option<PaletteColor>,
+}
+
+// indicators of the states the tab is in, named after their placeholders
+pub fn tab_indicators(
+    tab: &TabInfo,
+    has_failed_command: bool,
+    user_conf: &UserConfiguration,
+) -> Vec<(&'static str, Indicator)> {
+    let other_clients = tab.other_focused_clients.len();
+    let other_clients_indicator = Indicator {
+        text: format!("{}{other_clients}", user_conf.other_clients_indicator.text),
+        ..user_conf.other_clients_indicator.clone()
+    };
+    [
+        (
+            "failed",
+            has_failed_command,
+            &user_conf.failed_command_indicator,
+        ),
+        ("sync", tab.is_sync_panes_active, &user_conf.sync_indicator),
+        (
+            "fullscreen",
+            tab.is_fullscreen_active,
+            &user_conf.fullscreen_indicator,
+        ),
+        (
+            "floating",
+            tab.are_floating_panes_visible,
+            &user_conf.floating_indicator,
+        ),
+        (
+            "clients",
+            other_clients > 0 && !user_conf.other_clients_indicator.text.is_empty(),
+            &other_clients_indicator,
+        ),
+        (
+            "dirty",
+            tab.is_swap_layout_dirty,
+            &user_conf.swap_layout_dirty_indicator,
+        ),
+    ]
+    .into_iter()
+    .filter(|(_, active, indicator)| *active && !indicator.text.is_empty())
+    .map(|(name, _, indicator)| (name, indicator.clone()))
+    .collect()
+}
+
+// number of terminal panes in the tab and how many of them are floating, leaving out plugins
+// and panes that were suppressed
+fn terminal_counts(panes: &[PaneInfo]) -> (usize, usize) {
+    panes
+        .iter()
+        .filter(|pane| !pane.is_plugin && !pane.is_suppressed)
+        .fold((0, 0), |(terminals, floating), pane| {
+            (terminals + 1, floating + pane.is_floating as usize)
+        })
+}
+
+// ids of the command panes of a tab that exited with a non-zero code
+pub fn failed_command_panes(panes: &[PaneInfo]) -> impl Iterator<Item = u32> + '_ {
+    panes
+        .iter()
+        .filter(|pane| {
+            !pane.is_plugin
+                && (pane.exited || pane.is_held)
+                && pane.exit_status.is_some_and(|code| code != 0)
+        })
+        .map(|pane| pane.id)
+}
+
+// the focused pane of the tab, looking at the floating panes only when they're visible
+pub fn focused_pane<'a>(tab: &TabInfo, panes: &'a [PaneInfo]) -> Option<&'a PaneInfo> {
+    panes.iter().find(|pane| {
+        pane.is_focused && !pane.is_suppressed && pane.is_floating == tab.are_floating_panes_visible
+    })
+}
+
+// the command of the focused pane, or its title if it isn't a command pane
+fn focused_pane_title<'a>(tab: &TabInfo, panes: &'a [PaneInfo]) -> Option<&'a str> {
+    let pane = focused_pane(tab, panes)?;
+    Some(pane.terminal_command.as_deref().unwrap_or(&pane.title))
+}
+
+// names such as `Tab #3` that zellij gives to new tabs
+pub fn is_default_tab_name(name: &str) -> bool {
+    name.strip_prefix("Tab #")
+        .is_some_and(|index| index.parse::<u32>().is_ok())
+}
+
+// width of the name a tab is displayed with before it's truncated
+pub fn tab_name_width(name: &str, user_conf: &UserConfiguration) -> usize {
+    if is_default_tab_name(name) {
+        display_width(&user_conf.default_tab_name)
+    } else {
+        display_width(name)
+    }
+}
+
+/// What a tab label displays besides the tab itself
+pub struct TabContext<'a> {
+    pub panes: &'a [PaneInfo],
+    // failed command pane that wasn't seen yet
+    pub failed_pane: Option<u32>,
+    // working directory of the focused pane
+    pub cwd: Option<String>,
+    pub label: TabLabel,
+}
+
+/// How much of the label of an inactive tab is displayed, as less of it is needed for more tabs
+/// to fit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TabLabel {
+    Full,
+    // the name is shortened to this width
+    ShortenedName(usize),
+    IndexOnly,
+}
+
+pub fn render_tab(
+    text: String,
+    tab: &TabInfo,
+    context: TabContext,
+    format: &Template,
+    user_conf: &UserConfiguration,
+) -> LinePart {
+    let (foreground_color, background_color, attributes) = if tab.active {
+        (
+            user_conf.color_active_tab,
+            user_conf.color_active_tab_bg,
+            user_conf.active_tab_attributes,
+        )
     } else {
-        user_conf.color_tab
+        (
+            user_conf.color_tab,
+            user_conf.color_tab_bg,
+            user_conf.tab_attributes,
+        )
     };
 
     let tab_index = tab.position + 1;
@@ -22,49 +202,152 @@ pub fn render_tab(text: String, tab: &TabInfo, user_conf: UserConfiguration) ->
         .split_once("Tab #")
         .and_then(|(_, raw_index)| {
             let tab_name_index = raw_index.parse::<u32>().ok()?;
-            (text == format!("Tab #{tab_name_index}")).then_some(user_conf.default_tab_name)
+            (text == format!("Tab #{tab_name_index}")).then_some(user_conf.default_tab_name.clone())
         })
         .unwrap_or(text);
-
-    let tab_text = format!("{} {}", tab_index, text);
-    let tab_right_padding = " ";
-    let tab_left_padding = if tab.position == 0 {
-        ""
+    let TabContext {
+        panes,
+        failed_pane,
+        cwd,
+        label,
+    } = context;
+    let format = if label == TabLabel::IndexOnly && !tab.active {
+        &user_conf.index_only_tab_format
     } else {
-        tab_right_padding
+        format
+    };
+    let max_name_width = match (tab.active, label) {
+        (true, _) => user_conf.active_max_tab_name_width,
+        (false, TabLabel::ShortenedName(limit)) if user_conf.max_tab_name_width == 0 => limit,
+        (false, TabLabel::ShortenedName(limit)) => limit.min(user_conf.max_tab_name_width),
+        (false, _) => user_conf.max_tab_name_width,
+    };
+    let text = match max_name_width {
+        0 => text,
+        max_name_width => user_conf.tab_name_ellipsis.truncate(&text, max_name_width),
+    };
+
+    let cwd = cwd.unwrap_or_default();
+    let indicators = tab_indicators(tab, failed_pane.is_some(), user_conf);
+    let (terminals, floating_terminals) = terminal_counts(panes);
+    let pane_title = focused_pane_title(tab, panes)
+        .map(|title| match user_conf.pane_title_max_width {
+            0 => title.trim().to_string(),
+            max_width => truncate_end(title.trim(), max_width),
+        })
+        .unwrap_or_default();
+    let mut tab_styled_text = String::new();
+    let mut tab_text_len = 0;
+    let mut failed_pane_marker = None;
+    // returns the columns taken by the text within the tab
+    let mut paint = |text: &str, color: Option<PaletteColor>| {
+        let start = tab_text_len;
+        tab_text_len += display_width(text);
+        tab_styled_text += &attributes
+            .apply(style!(color.unwrap_or(foreground_color), background_color))
+            .paint(text)
+            .to_string();
+        start..tab_text_len
     };
-    let tab_text = format!("{tab_left_padding}{tab_text}{tab_right_padding}");
-    let tab_text_len = tab_text.len();
-    let tab_styled_text = style!(foreground_color, background_color)
-        .bold()
-        .paint(tab_text);
+
+    let is_true = |condition: &str| match condition {
+        "first" => tab.position == 0,
+        "terminals" => terminals > 0,
+        "floating_terminals" => floating_terminals > 0,
+        "pane_title" => !pane_title.is_empty(),
+        "cwd" => !cwd.is_empty(),
+        "indicators" => !indicators.is_empty(),
+        _ => indicators.iter().any(|(name, _)| *name == condition),
+    };
+    for piece in format.pieces(&is_true) {
+        match piece {
+            Piece::Text(text) => {
+                paint(text, None);
+            }
+            Piece::Field("index") => {
+                paint(&tab_index.to_string(), None);
+            }
+            Piece::Field("name") => {
+                paint(&text, None);
+            }
+            Piece::Field("panes") => {
+                let panes = tab.selectable_tiled_panes_count + tab.selectable_floating_panes_count;
+                paint(&panes.to_string(), None);
+            }
+            Piece::Field("terminals") => {
+                paint(&terminals.to_string(), None);
+            }
+            Piece::Field("floating_terminals") => {
+                paint(&floating_terminals.to_string(), None);
+            }
+            Piece::Field("pane_title") => {
+                paint(&pane_title, None);
+            }
+            Piece::Field("cwd") => {
+                paint(&cwd, None);
+            }
+            Piece::Field("indicators") => {
+                for (i, (name, indicator)) in indicators.iter().enumerate() {
+                    if i > 0 {
+                        paint(" ", None);
+                    }
+                    let columns = paint(&indicator.text, indicator.color);
+                    if *name == "failed" {
+                        failed_pane_marker = failed_pane.map(|id| (columns, id));
+                    }
+                }
+            }
+            Piece::Field(field) => {
+                if let Some((name, indicator)) = indicators.iter().find(|(name, _)| *name == field)
+                {
+                    let columns = paint(&indicator.text, indicator.color);
+                    if *name == "failed" {
+                        failed_pane_marker = failed_pane.map(|id| (columns, id));
+                    }
+                }
+            }
+        }
+    }
 
     LinePart {
-        part: tab_styled_text.to_string(),
+        part: tab_styled_text,
         len: tab_text_len,
         tab_index: Some(tab.position),
+        kind: LinePartKind::Tab,
+        bg: background_color,
+        failed_pane_marker,
     }
 }
 
-pub fn tab_style(mut tabname: String, tab: &TabInfo, user_conf: UserConfiguration) -> LinePart {
-    if tab.is_sync_panes_active {
-        tabname.push_str(" (Sync)");
-    }
+pub fn tab_style(
+    tabname: String,
+    tab: &TabInfo,
+    context: TabContext,
+    user_conf: &UserConfiguration,
+) -> LinePart {
+    let format = if tab.active {
+        &user_conf.active_tab_format
+    } else {
+        &user_conf.tab_format
+    };
 
-    render_tab(tabname, tab, user_conf)
+    render_tab(tabname, tab, context, format, user_conf)
 }
 
-pub(crate) fn get_tab_to_focus(
+// the failed command pane whose marker was clicked
+pub(crate) fn get_clicked_failed_pane(
     tab_line: &[LinePart],
-    active_tab_idx: usize,
     mouse_click_col: usize,
-) -> Option<usize> {
-    let clicked_line_part = get_clicked_line_part(tab_line, mouse_click_col)?;
-    let clicked_tab_idx = clicked_line_part.tab_index?;
-    // tabs are indexed starting from 1 so we need to add 1
-    let clicked_tab_idx = clicked_tab_idx + 1;
-    if clicked_tab_idx != active_tab_idx {
-        return Some(clicked_tab_idx);
+) -> Option<u32> {
+    let mut len = 0;
+    for tab_line_part in tab_line {
+        if mouse_click_col >= len && mouse_click_col < len + tab_line_part.len {
+            let (columns, pane_id) = tab_line_part.failed_pane_marker.as_ref()?;
+            return columns
+                .contains(&(mouse_click_col - len))
+                .then_some(*pane_id);
+        }
+        len += tab_line_part.len;
     }
     None
 }
@@ -0,0 +1,97 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+// columns taken by a grapheme, since emoji sequences are drawn as a single wide character
+fn grapheme_width(grapheme: &str) -> usize {
+    if grapheme.contains('\u{fe0f}') {
+        return 2;
+    }
+    grapheme.width().min(2)
+}
+
+/// Columns the text takes in the terminal
+pub fn display_width(text: &str) -> usize {
+    text.graphemes(true).map(grapheme_width).sum()
+}
+
+/// Where `…` goes in truncated text
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Ellipsis {
+    Start,
+    Middle,
+    #[default]
+    End,
+}
+
+impl Ellipsis {
+    pub fn parse(ellipsis: &str) -> Result<Self, String> {
+        Ok(match ellipsis.trim() {
+            "start" => Self::Start,
+            "middle" => Self::Middle,
+            "end" => Self::End,
+            _ => return Err("expected start, middle or end".to_string()),
+        })
+    }
+
+    pub fn truncate(self, text: &str, max_width: usize) -> String {
+        match self {
+            Self::Start => truncate_start(text, max_width),
+            Self::Middle => truncate_middle(text, max_width),
+            Self::End => truncate_end(text, max_width),
+        }
+    }
+}
+
+/// Cuts the text down to `max_width` columns, starting it with `…` when it doesn't fit
+pub fn truncate_start(text: &str, max_width: usize) -> String {
+    if display_width(text) <= max_width {
+        return text.to_string();
+    }
+    if max_width == 0 {
+        return String::new();
+    }
+    let mut end = take_width(text.graphemes(true).rev(), max_width - 1);
+    end.reverse();
+    format!("…{}", end.concat())
+}
+
+/// Cuts the text down to `max_width` columns, ending it with `…` when it doesn't fit
+pub fn truncate_end(text: &str, max_width: usize) -> String {
+    if display_width(text) <= max_width {
+        return text.to_string();
+    }
+    if max_width == 0 {
+        return String::new();
+    }
+    let mut truncated = take_width(text.graphemes(true), max_width - 1).concat();
+    truncated.push('…');
+    truncated
+}
+
+/// Cuts the text down to `max_width` columns, replacing its middle with `…` when it doesn't fit
+pub fn truncate_middle(text: &str, max_width: usize) -> String {
+    if display_width(text) <= max_width {
+        return text.to_string();
+    }
+    if max_width == 0 {
+        return String::new();
+    }
+    // the end gets the extra column when the remaining width is odd
+    let start_width = (max_width - 1) / 2;
+    let end_width = max_width - 1 - start_width;
+    let start = take_width(text.graphemes(true), start_width).concat();
+    let mut end = take_width(text.graphemes(true).rev(), end_width);
+    end.reverse();
+    format!("{start}…{}", end.concat())
+}
+
+// the graphemes that fit in `max_width` columns, in the order they're taken
+fn take_width<'a>(graphemes: impl Iterator<Item = &'a str>, max_width: usize) -> Vec<&'a str> {
+    let mut width = 0;
+    graphemes
+        .take_while(|grapheme| {
+            width += grapheme_width(grapheme);
+            width <= max_width
+        })
+        .collect()
+}